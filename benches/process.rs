@@ -0,0 +1,40 @@
+use biquad_filter::{Biquad, Cascade, FilterType};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+fn process(c: &mut Criterion) {
+    let input: Vec<f32> = (0..4096).map(|i| (i as f32 * 0.01).sin()).collect();
+    let mut output = vec![0.; input.len()];
+
+    let mut filter = Biquad::<f32>::default();
+    filter.prepare(48000).unwrap();
+    filter.set(FilterType::Peak, 1000., 6., 1.).unwrap();
+
+    c.bench_function("tick", |b| {
+        b.iter(|| {
+            for (out_sample, in_sample) in output.iter_mut().zip(&input) {
+                *out_sample = filter.tick(black_box(*in_sample));
+            }
+        })
+    });
+
+    c.bench_function("process", |b| {
+        b.iter(|| filter.process(black_box(&input), black_box(&mut output)))
+    });
+}
+
+fn cascade(c: &mut Criterion) {
+    let mut buffer: Vec<f32> = (0..1 << 16).map(|i| (i as f32 * 0.01).sin()).collect();
+    let mut cascade = Cascade::<f32>::butterworth_lowpass(16, 48000, 5000.).unwrap();
+
+    c.bench_function("cascade stage-major", |b| {
+        b.iter(|| cascade.process_in_place(black_box(&mut buffer)))
+    });
+
+    c.bench_function("cascade fused", |b| {
+        b.iter(|| cascade.process_fused(black_box(&mut buffer)))
+    });
+}
+
+criterion_group!(benches, process, cascade);
+criterion_main!(benches);
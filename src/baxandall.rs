@@ -0,0 +1,115 @@
+use crate::{Biquad, BiquadError, Coefficients, MAX_GAIN_DB};
+use num_traits::Float;
+
+/// Where the bass control reaches half of its gain.
+const BASS_FREQUENCY: f64 = 250.;
+/// Where the treble control reaches half of its gain.
+const TREBLE_FREQUENCY: f64 = 4000.;
+
+impl<F: Float> Coefficients<F> {
+    /// Sets the bass control of a Baxandall tone stack, a first order low shelf.
+    ///
+    /// The cookbook shelves move between their levels within about two octaves. The analog tone
+    /// control rises at no more than 6 dB per octave and so spreads its transition over many
+    /// octaves, which is the gentle sound of hi-fi tone controls. The shelf reaches half of
+    /// `gain_db` at 250 Hz and the bilinear transform is prewarped there.
+    pub fn set_baxandall_bass(&mut self, gain_db: f64) -> Result<(), BiquadError> {
+        self.set_first_order_lowshelf(BASS_FREQUENCY, gain_db)
+    }
+
+    /// Sets the treble control of a Baxandall tone stack, a first order high shelf reaching half
+    /// of `gain_db` at 4 kHz. See `set_baxandall_bass`.
+    pub fn set_baxandall_treble(&mut self, gain_db: f64) -> Result<(), BiquadError> {
+        self.set_first_order_highshelf(TREBLE_FREQUENCY, gain_db)
+    }
+
+    /// A first order low shelf with half of `gain_db` at `frequency`.
+    pub(crate) fn set_first_order_lowshelf(
+        &mut self,
+        frequency: f64,
+        gain_db: f64,
+    ) -> Result<(), BiquadError> {
+        let (k, g) = self.first_order_shelf(frequency, gain_db)?;
+        // (s + ωg) / (s + ω/g)
+        self.store(1. + k * g, k * g - 1., 0., 1. + k / g, k / g - 1., 0.)
+    }
+
+    /// A first order high shelf with half of `gain_db` at `frequency`.
+    pub(crate) fn set_first_order_highshelf(
+        &mut self,
+        frequency: f64,
+        gain_db: f64,
+    ) -> Result<(), BiquadError> {
+        let (k, g) = self.first_order_shelf(frequency, gain_db)?;
+        // g·(sg + ω) / (s + ωg)
+        self.store(g * (g + k), g * (k - g), 0., 1. + k * g, k * g - 1., 0.)
+    }
+
+    /// The prewarped corner and the square root of the linear gain.
+    fn first_order_shelf(&self, frequency: f64, gain_db: f64) -> Result<(f64, f64), BiquadError> {
+        let omega = self.omega(frequency)?;
+        if gain_db.abs() > MAX_GAIN_DB {
+            return Err(BiquadError::GainOutOfRange);
+        }
+        Ok((f64::tan(omega / 2.), f64::powf(10., gain_db / 40.)))
+    }
+}
+
+impl<F: Float> Biquad<F> {
+    pub fn set_baxandall_bass(&mut self, gain_db: f64) -> Result<(), BiquadError> {
+        self.coefficients.set_baxandall_bass(gain_db)?;
+        self.settings = None;
+        Ok(())
+    }
+
+    pub fn set_baxandall_treble(&mut self, gain_db: f64) -> Result<(), BiquadError> {
+        self.coefficients.set_baxandall_treble(gain_db)?;
+        self.settings = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FilterType, DEFAULT_Q};
+
+    /// The steepest change of the magnitude in dB per octave.
+    fn max_slope(coefficients: &Coefficients<f64>) -> f64 {
+        (0..100)
+            .map(|i| 20. * f64::exp2(i as f64 / 10.))
+            .map(|f| (coefficients.magnitude_db(2. * f) - coefficients.magnitude_db(f)).abs())
+            .fold(0., f64::max)
+    }
+
+    #[test]
+    fn bass_is_gentler_than_cookbook_shelf() {
+        let mut baxandall = Coefficients::<f64>::default();
+        baxandall.set_sample_rate(48000).unwrap();
+        let mut cookbook = baxandall.clone();
+        baxandall.set_baxandall_bass(12.).unwrap();
+        cookbook
+            .set(FilterType::Lowshelf, 250., 12., DEFAULT_Q)
+            .unwrap();
+
+        assert!((baxandall.magnitude_db(20.) - 12.).abs() < 0.5);
+        assert!((baxandall.magnitude_db(250.) - 6.).abs() < 1e-6);
+        assert!(baxandall.magnitude_db(20000.).abs() < 0.1);
+
+        assert!(max_slope(&baxandall) < 6.);
+        assert!(max_slope(&cookbook) > 1.5 * max_slope(&baxandall));
+        // two octaves above the midpoint the cookbook shelf is flat, the tone control is not yet
+        assert!(cookbook.magnitude_db(1000.) < 0.1);
+        assert!(baxandall.magnitude_db(1000.) > 0.5);
+    }
+
+    #[test]
+    fn treble_boosts_highs() {
+        let mut treble = Coefficients::<f64>::default();
+        treble.set_sample_rate(48000).unwrap();
+        treble.set_baxandall_treble(-9.).unwrap();
+        assert!((treble.magnitude_db(4000.) + 4.5).abs() < 1e-6);
+        assert!(treble.magnitude_db(20.).abs() < 0.01);
+        assert!(treble.magnitude_db(24000.) < -8.);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::Biquad;
+use num_traits::Float;
+
+/// A buffer of samples any `Biquad` can filter, converting the samples to its float type and
+/// back on the fly, for hosts that hand over f32 or f64 buffers.
+pub trait ProcessBuffer {
+    /// Filters the buffer in place, see `Biquad::process_buffer`.
+    fn process_through<F: Float>(self, filter: &mut Biquad<F>);
+}
+
+impl ProcessBuffer for &mut [f32] {
+    fn process_through<F: Float>(self, filter: &mut Biquad<F>) {
+        filter.process_converted(self);
+    }
+}
+
+impl ProcessBuffer for &mut [f64] {
+    fn process_through<F: Float>(self, filter: &mut Biquad<F>) {
+        filter.process_converted(self);
+    }
+}
+
+impl<F: Float> Biquad<F> {
+    /// Like `process_in_place`, but for a buffer of f32 or f64 samples regardless of `F`.
+    ///
+    /// The samples are converted to `F` for the recursion and the output is rounded back, so an
+    /// f32 buffer through a `Biquad<f64>` gets the precision of the f64 state.
+    pub fn process_buffer(&mut self, buffer: impl ProcessBuffer) {
+        buffer.process_through(self);
+    }
+
+    fn process_converted<T: Float>(&mut self, buffer: &mut [T]) {
+        self.process_segments(buffer.len(), |filter, range| {
+            for sample in &mut buffer[range] {
+                let input = F::from(*sample).unwrap_or_else(F::zero);
+                *sample = T::from(filter.tick(input)).unwrap_or_else(T::zero);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signal, FilterType};
+
+    #[test]
+    fn filters_f32_and_f64_buffers() {
+        let mut filter = Biquad::<f64>::default();
+        filter.prepare(48000).unwrap();
+        filter.set(FilterType::Peak, 1000., 6., 2.).unwrap();
+        let input: Vec<f32> = signal::white_noise(512, 7)
+            .iter()
+            .map(|x| *x as f32)
+            .collect();
+
+        let mut wide: Vec<f64> = input.iter().map(|x| *x as f64).collect();
+        let expected = filter.clone().process_to_vec(&wide);
+        filter.clone().process_buffer(wide.as_mut_slice());
+        assert_eq!(wide, expected);
+
+        let mut narrow = input.clone();
+        filter.process_buffer(narrow.as_mut_slice());
+        for (y, expected) in narrow.iter().zip(&expected) {
+            assert_eq!(*y, *expected as f32);
+        }
+    }
+}
@@ -0,0 +1,529 @@
+use crate::{Biquad, BiquadError, Coefficients, FilterSettings, FilterType};
+use num_traits::Float;
+use std::f64::consts::PI;
+use std::ops::Shr;
+
+/// The start of the byte format of `Cascade::to_bytes`.
+const MAGIC: &[u8; 4] = b"BQCS";
+const VERSION: u32 = 1;
+/// magic, version, sample rate and number of stages
+const HEADER_LEN: usize = 16;
+const STAGE_LEN: usize = 5 * 8;
+
+/// Biquads in series.
+#[derive(Default, Debug, Clone)]
+pub struct Cascade<F: Float> {
+    stages: Vec<Biquad<F>>,
+}
+
+impl<F: Float> Cascade<F> {
+    pub fn new(stages: Vec<Biquad<F>>) -> Self {
+        Self { stages }
+    }
+
+    /// A Butterworth lowpass of an even order, built from biquads with staggered Q.
+    pub fn butterworth_lowpass(
+        order: usize,
+        sample_rate: u32,
+        cutoff: f64,
+    ) -> Result<Self, BiquadError> {
+        if order == 0 || !order.is_multiple_of(2) {
+            return Err(BiquadError::UnsupportedOrder);
+        }
+        let sections = (1..=order / 2).map(|k| {
+            let q = 1. / (2. * f64::cos((2 * k - 1) as f64 * PI / (2 * order) as f64));
+            (1., q)
+        });
+        Self::lowpass_sections(sections, sample_rate, cutoff)
+    }
+
+    /// A Bessel lowpass of order 2 or 4, with -3 dB at `cutoff`.
+    ///
+    /// Bessel filters have a nearly constant group delay in the passband, so the step response
+    /// has almost no overshoot, at the cost of a slower rolloff than Butterworth filters.
+    pub fn bessel_lowpass(
+        order: usize,
+        sample_rate: u32,
+        cutoff: f64,
+    ) -> Result<Self, BiquadError> {
+        // natural frequency relative to the cutoff and Q of each section
+        let sections: &[(f64, f64)] = match order {
+            2 => &[(1.27201964951, 0.57735026919)],
+            4 => &[(1.41948147, 0.52193418), (1.59100550, 0.80553617)],
+            _ => return Err(BiquadError::UnsupportedOrder),
+        };
+        Self::lowpass_sections(sections.iter().copied(), sample_rate, cutoff)
+    }
+
+    /// A Chebyshev type I lowpass of an even order, with `ripple_db` of passband ripple.
+    ///
+    /// The gain ripples between 0 dB and `ripple_db` up to `cutoff`, the edge of the passband,
+    /// and rolls off faster than a Butterworth filter of the same order above it. A ripple that
+    /// isn't positive fails with `BiquadError::InvalidRipple`.
+    pub fn chebyshev_lowpass(
+        order: usize,
+        sample_rate: u32,
+        cutoff: f64,
+        ripple_db: f64,
+    ) -> Result<Self, BiquadError> {
+        if order == 0 || !order.is_multiple_of(2) {
+            return Err(BiquadError::UnsupportedOrder);
+        }
+        if ripple_db.is_nan() || ripple_db <= 0. {
+            return Err(BiquadError::InvalidRipple);
+        }
+        let epsilon = f64::sqrt(f64::powf(10., ripple_db / 10.) - 1.);
+        let mu = f64::asinh(1. / epsilon) / order as f64;
+        // the sections are prewarped together, so the ripple of the analog prototype stays
+        // intact under the bilinear transform
+        let warped_cutoff = f64::tan(PI * cutoff / sample_rate as f64);
+        // the poles are on an ellipse with the half axes sinh(μ) and cosh(μ)
+        let sections = (1..=order / 2).map(|k| {
+            let theta = (2 * k - 1) as f64 * PI / (2 * order) as f64;
+            let (re, im) = (mu.sinh() * theta.sin(), mu.cosh() * theta.cos());
+            let natural = f64::hypot(re, im);
+            let frequency = f64::atan(natural * warped_cutoff) * sample_rate as f64 / PI;
+            (frequency / cutoff, natural / (2. * re))
+        });
+        Self::lowpass_sections(sections, sample_rate, cutoff)
+    }
+
+    /// A Butterworth lowpass with a rolloff of `db_per_octave`, which has to be a multiple of 12.
+    pub fn lowpass_slope(
+        db_per_octave: u32,
+        sample_rate: u32,
+        cutoff: f64,
+    ) -> Result<Self, BiquadError> {
+        if db_per_octave == 0 || !db_per_octave.is_multiple_of(12) {
+            return Err(BiquadError::UnsupportedSlope);
+        }
+        Self::butterworth_lowpass(db_per_octave as usize / 6, sample_rate, cutoff)
+    }
+
+    /// One stage per entry of `settings`, in order.
+    pub fn from_settings(
+        settings: &[FilterSettings],
+        sample_rate: u32,
+    ) -> Result<Self, BiquadError> {
+        let stages = settings
+            .iter()
+            .map(|settings| {
+                let mut stage = Biquad::default();
+                stage.prepare(sample_rate)?;
+                stage.set(
+                    settings.filter_type,
+                    settings.frequency,
+                    settings.gain_db,
+                    settings.q,
+                )?;
+                Ok(stage)
+            })
+            .collect::<Result<_, BiquadError>>()?;
+        Ok(Self { stages })
+    }
+
+    /// Serializes the coefficients into a compact versioned format.
+    ///
+    /// The layout, with all numbers little endian:
+    ///
+    /// | bytes | content |
+    /// |-------|---------|
+    /// | 0-3 | `BQCS` |
+    /// | 4-7 | format version, `1`, u32 |
+    /// | 8-11 | sample rate in Hz, u32 |
+    /// | 12-15 | number of stages, u32 |
+    /// | 16- | `[b0, b1, b2, a1, a2]` of each stage as f64, normalized to `a0 = 1` |
+    ///
+    /// The sample rate is the one of the first stage, 0 for an empty cascade. The filter state
+    /// is not included.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let sample_rate = self
+            .stages
+            .first()
+            .and_then(|stage| stage.coefficients.sample_rate.to_u32())
+            .unwrap_or(0);
+        let mut bytes = Vec::with_capacity(HEADER_LEN + STAGE_LEN * self.stages.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(self.stages.len() as u32).to_le_bytes());
+        for stage in &self.stages {
+            let [b0, b1, b2, a0, a1, a2] = stage.coefficients.as_f64();
+            for c in [b0, b1, b2, a1, a2] {
+                bytes.extend_from_slice(&(c / a0).to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Reads a cascade written by `to_bytes`, with cleared state.
+    ///
+    /// Data that is cut off, has trailing bytes, another format or another version fails with
+    /// `BiquadError::InvalidData`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BiquadError> {
+        let (header, body) = bytes
+            .split_at_checked(HEADER_LEN)
+            .ok_or(BiquadError::InvalidData)?;
+        let word = |index: usize| {
+            u32::from_le_bytes([
+                header[index],
+                header[index + 1],
+                header[index + 2],
+                header[index + 3],
+            ])
+        };
+        let stages = word(12) as usize;
+        if &header[..4] != MAGIC
+            || word(4) != VERSION
+            || Some(body.len()) != stages.checked_mul(STAGE_LEN)
+        {
+            return Err(BiquadError::InvalidData);
+        }
+
+        let stages = body
+            .chunks_exact(STAGE_LEN)
+            .map(|stage| {
+                let mut c = stage
+                    .chunks_exact(8)
+                    .map(|c| f64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]));
+                let mut next = || c.next().ok_or(BiquadError::InvalidData);
+                let (b0, b1, b2, a1, a2) = (next()?, next()?, next()?, next()?, next()?);
+                let mut coefficients = Coefficients::default();
+                coefficients.set_sample_rate(word(8))?;
+                coefficients.store(b0, b1, b2, 1., a1, a2)?;
+                let mut stage = Biquad::default();
+                stage.set_coefficients(coefficients);
+                Ok(stage)
+            })
+            .collect::<Result<_, BiquadError>>()?;
+        Ok(Self { stages })
+    }
+
+    fn lowpass_sections(
+        sections: impl Iterator<Item = (f64, f64)>,
+        sample_rate: u32,
+        cutoff: f64,
+    ) -> Result<Self, BiquadError> {
+        let stages = sections
+            .map(|(ratio, q)| {
+                let mut stage = Biquad::default();
+                stage.prepare(sample_rate)?;
+                stage.set(FilterType::Lowpass, ratio * cutoff, 0., q)?;
+                Ok(stage)
+            })
+            .collect::<Result<_, BiquadError>>()?;
+        Ok(Self { stages })
+    }
+
+    pub fn stages(&self) -> &[Biquad<F>] {
+        &self.stages
+    }
+
+    pub fn stages_mut(&mut self) -> &mut [Biquad<F>] {
+        &mut self.stages
+    }
+
+    /// The magnitude response of all stages together at the given frequency in dB.
+    pub fn magnitude_db(&self, frequency: f64) -> f64 {
+        self.stages
+            .iter()
+            .map(|stage| stage.coefficients.magnitude_db(frequency))
+            .sum()
+    }
+
+    /// The peak to peak variation of the magnitude in dB between the edges of `passband` in Hz,
+    /// sampled at `resolution` log spaced frequencies.
+    pub fn passband_ripple_db(&self, passband: (f64, f64), resolution: usize) -> f64 {
+        let (low, high) = passband;
+        let points = resolution.max(2);
+        let (min, max) = (0..points)
+            .map(|point| low * (high / low).powf(point as f64 / (points - 1) as f64))
+            .map(|frequency| self.magnitude_db(frequency))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), db| {
+                (min.min(db), max.max(db))
+            });
+        max - min
+    }
+
+    /// The group delay of all stages together at the given frequency in samples.
+    pub fn group_delay(&self, frequency: f64) -> f64 {
+        self.stages
+            .iter()
+            .map(|stage| stage.coefficients.group_delay(frequency))
+            .sum()
+    }
+
+    pub fn process(&mut self, input: &[F], output: &mut [F]) {
+        let len = input.len().min(output.len());
+        output[..len].copy_from_slice(&input[..len]);
+        self.process_in_place(&mut output[..len]);
+    }
+
+    pub fn process_in_place(&mut self, buffer: &mut [F]) {
+        self.stages
+            .iter_mut()
+            .for_each(|stage| stage.process_in_place(buffer));
+    }
+
+    /// Like `process_in_place`, but runs each sample through all stages before the next one.
+    ///
+    /// The buffer is only traversed once, which keeps it in cache for long buffers and many
+    /// stages, while `process_in_place` can keep each stage in registers. The output is the same,
+    /// but as with `Biquad::tick` scheduled events are not applied.
+    pub fn process_fused(&mut self, buffer: &mut [F]) {
+        for sample in buffer {
+            *sample = self
+                .stages
+                .iter_mut()
+                .fold(*sample, |sample, stage| stage.tick(sample));
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.stages.iter_mut().for_each(Biquad::reset);
+    }
+}
+
+/// `a >> b` runs `a` first, then `b`.
+impl<F: Float> Shr<Biquad<F>> for Biquad<F> {
+    type Output = Cascade<F>;
+
+    fn shr(self, next: Biquad<F>) -> Cascade<F> {
+        Cascade::new(vec![self, next])
+    }
+}
+
+impl<F: Float> Shr<Cascade<F>> for Biquad<F> {
+    type Output = Cascade<F>;
+
+    fn shr(self, mut next: Cascade<F>) -> Cascade<F> {
+        next.stages.insert(0, self);
+        next
+    }
+}
+
+impl<F: Float> Shr<Biquad<F>> for Cascade<F> {
+    type Output = Cascade<F>;
+
+    fn shr(mut self, next: Biquad<F>) -> Cascade<F> {
+        self.stages.push(next);
+        self
+    }
+}
+
+impl<F: Float> Shr<Cascade<F>> for Cascade<F> {
+    type Output = Cascade<F>;
+
+    fn shr(mut self, next: Cascade<F>) -> Cascade<F> {
+        self.stages.extend(next.stages);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overshoot(mut cascade: Cascade<f64>) -> f64 {
+        let mut step = vec![1.; 4096];
+        cascade.process_in_place(&mut step);
+        step.iter().copied().fold(f64::MIN, f64::max) - 1.
+    }
+
+    #[test]
+    fn bessel_has_less_overshoot_than_butterworth() {
+        for order in [2, 4] {
+            let bessel = overshoot(Cascade::bessel_lowpass(order, 48000, 1000.).unwrap());
+            let butterworth = overshoot(Cascade::butterworth_lowpass(order, 48000, 1000.).unwrap());
+            assert!(bessel < 0.01);
+            assert!(butterworth > 0.04);
+        }
+    }
+
+    #[test]
+    fn from_settings_builds_each_stage() {
+        let settings = [
+            FilterSettings {
+                filter_type: FilterType::Highpass,
+                frequency: 40.,
+                gain_db: 0.,
+                q: 0.707,
+            },
+            FilterSettings {
+                filter_type: FilterType::Peak,
+                frequency: 1000.,
+                gain_db: 4.,
+                q: 1.,
+            },
+            FilterSettings {
+                filter_type: FilterType::Highshelf,
+                frequency: 8000.,
+                gain_db: -6.,
+                q: 0.707,
+            },
+        ];
+        let cascade = Cascade::<f64>::from_settings(&settings, 48000).unwrap();
+        assert_eq!(cascade.stages().len(), 3);
+        let expected: f64 = cascade
+            .stages()
+            .iter()
+            .map(|stage| stage.coefficients().magnitude_db(1000.))
+            .sum();
+        assert!((cascade.magnitude_db(1000.) - expected).abs() < 1e-9);
+        assert!((cascade.magnitude_db(1000.) - 4.).abs() < 0.1);
+        assert!((cascade.magnitude_db(24000.) + 6.).abs() < 0.2);
+        assert!(cascade.magnitude_db(10.) < -20.);
+
+        let mut invalid = settings;
+        invalid[1].frequency = 30000.;
+        assert!(matches!(
+            Cascade::<f64>::from_settings(&invalid, 48000),
+            Err(BiquadError::FrequencyOverNyqist)
+        ));
+    }
+
+    #[test]
+    fn chebyshev_ripples_butterworth_is_flat() {
+        let butterworth = Cascade::<f64>::butterworth_lowpass(4, 48000, 1000.).unwrap();
+        assert!(butterworth.passband_ripple_db((20., 200.), 512) < 0.01);
+
+        for ripple_db in [0.5, 1., 3.] {
+            let chebyshev = Cascade::<f64>::chebyshev_lowpass(4, 48000, 1000., ripple_db).unwrap();
+            let ripple = chebyshev.passband_ripple_db((20., 900.), 2048);
+            assert!((ripple - ripple_db).abs() < 0.01 * ripple_db);
+            // the passband ends at the cutoff, where the gain falls below the ripple
+            assert!(chebyshev.magnitude_db(1100.) < -ripple_db);
+            assert!(chebyshev.magnitude_db(2000.) < butterworth.magnitude_db(2000.));
+        }
+
+        assert!(matches!(
+            Cascade::<f64>::chebyshev_lowpass(4, 48000, 1000., 0.),
+            Err(BiquadError::InvalidRipple)
+        ));
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut cascade = Cascade::<f32>::chebyshev_lowpass(4, 44100, 5000., 1.).unwrap();
+        let mut peak = Biquad::default();
+        peak.prepare(44100).unwrap();
+        peak.set(FilterType::Peak, 300., -4., 2.).unwrap();
+        cascade = cascade >> peak;
+
+        let bytes = cascade.to_bytes();
+        assert_eq!(bytes.len(), 16 + 3 * 40);
+        assert_eq!(&bytes[..4], b"BQCS");
+        let read = Cascade::<f32>::from_bytes(&bytes).unwrap();
+        assert_eq!(read.stages().len(), 3);
+        for (read, stage) in read.stages().iter().zip(cascade.stages()) {
+            assert_eq!(read.coefficients(), stage.coefficients());
+        }
+
+        assert!(
+            Cascade::<f64>::from_bytes(&Cascade::<f64>::default().to_bytes())
+                .unwrap()
+                .stages()
+                .is_empty()
+        );
+        for invalid in [&bytes[..bytes.len() - 1], &bytes[..10], b"BQCX"] {
+            assert!(matches!(
+                Cascade::<f32>::from_bytes(invalid),
+                Err(BiquadError::InvalidData)
+            ));
+        }
+        let mut version = bytes.clone();
+        version[4] = 2;
+        assert!(matches!(
+            Cascade::<f32>::from_bytes(&version),
+            Err(BiquadError::InvalidData)
+        ));
+    }
+
+    #[test]
+    fn bessel_cutoff_is_minus_three_db() {
+        let cascade = Cascade::<f64>::bessel_lowpass(4, 48000, 1000.).unwrap();
+        assert!((cascade.magnitude_db(1000.) + 3.).abs() < 0.1);
+    }
+
+    #[test]
+    fn unsupported_orders() {
+        assert!(matches!(
+            Cascade::<f32>::bessel_lowpass(3, 48000, 1000.),
+            Err(BiquadError::UnsupportedOrder)
+        ));
+        assert!(matches!(
+            Cascade::<f32>::butterworth_lowpass(5, 48000, 1000.),
+            Err(BiquadError::UnsupportedOrder)
+        ));
+    }
+
+    #[test]
+    fn lowpass_slope_rolloff() {
+        for db_per_octave in [12, 24, 48] {
+            let cascade = Cascade::<f64>::lowpass_slope(db_per_octave, 48000, 1000.).unwrap();
+            assert_eq!(cascade.stages().len() as u32, db_per_octave / 12);
+            assert!((cascade.magnitude_db(1000.) + 3.).abs() < 0.1);
+
+            let rolloff = cascade.magnitude_db(2000.) - cascade.magnitude_db(4000.);
+            assert!((rolloff - db_per_octave as f64).abs() < 1.5);
+        }
+
+        assert!(matches!(
+            Cascade::<f64>::lowpass_slope(18, 48000, 1000.),
+            Err(BiquadError::UnsupportedSlope)
+        ));
+    }
+
+    #[test]
+    fn group_delays_add_up() {
+        let mut stage = Biquad::<f64>::default();
+        stage.prepare(48000).unwrap();
+        stage.set(FilterType::Allpass, 2000., 0., 1.).unwrap();
+        let single = stage.coefficients().group_delay(1500.);
+        let cascade = Cascade::new(vec![stage.clone(), stage]);
+        assert!(single > 1.);
+        assert!((cascade.group_delay(1500.) - 2. * single).abs() < 1e-12);
+    }
+
+    #[test]
+    fn fused_matches_stage_major() {
+        let input: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.37).sin()).collect();
+        let mut cascade = Cascade::<f32>::butterworth_lowpass(8, 48000, 3000.).unwrap();
+
+        let mut fused = input.clone();
+        cascade.clone().process_fused(&mut fused);
+        let mut expected = vec![0.; input.len()];
+        cascade.process(&input, &mut expected);
+        assert_eq!(fused, expected);
+    }
+
+    #[test]
+    fn shr_chains_in_order() {
+        let filter = |filter_type: FilterType, frequency: f64| {
+            let mut filter = Biquad::<f64>::default();
+            filter.prepare(48000).unwrap();
+            filter.set(filter_type, frequency, 0., 0.707).unwrap();
+            filter
+        };
+        let (lowpass, highpass) = (
+            filter(FilterType::Lowpass, 5000.),
+            filter(FilterType::Highpass, 200.),
+        );
+        let notch = filter(FilterType::Notch, 1000.);
+        let input: Vec<f64> = (0..200).map(|i| (i as f64 * 0.3).sin()).collect();
+
+        let mut chained = lowpass.clone() >> highpass.clone();
+        let mut manual = Cascade::new(vec![lowpass.clone(), highpass.clone()]);
+        let (mut a, mut b) = (vec![0.; 200], vec![0.; 200]);
+        chained.process(&input, &mut a);
+        manual.process(&input, &mut b);
+        assert_eq!(a, b);
+
+        let chained = notch.clone() >> (lowpass.clone() >> highpass.clone()) >> notch.clone();
+        let stages: Vec<_> = chained.stages().iter().map(Biquad::coefficients).collect();
+        assert_eq!(
+            stages,
+            [notch.clone(), lowpass, highpass, notch].map(|stage| stage.coefficients())
+        );
+    }
+}
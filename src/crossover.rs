@@ -0,0 +1,153 @@
+use crate::{Biquad, BiquadError, Cascade, FilterType};
+use num_traits::Float;
+
+/// A Linkwitz-Riley crossover, which splits a signal into a lowpass and a highpass band that
+/// are both 6 dB down at the crossover frequency.
+///
+/// Each band is a squared Butterworth filter: of first order for LR2 and of second order for
+/// LR4. The bands of an LR4 crossover are in phase at every frequency and sum to an allpass, so
+/// the magnitude of the sum is flat. The bands of an LR2 crossover are 180° apart instead and
+/// would cancel at the crossover frequency, so `tick` and `process` invert the high band of LR2
+/// to make it sum flat as well.
+#[derive(Debug, Clone)]
+pub struct LinkwitzRiley<F: Float> {
+    low: Cascade<F>,
+    high: Cascade<F>,
+    order: usize,
+    frequency: f64,
+}
+
+impl<F: Float> LinkwitzRiley<F> {
+    /// Creates a crossover of order 2 or 4 at `frequency`.
+    pub fn new(order: usize, sample_rate: u32, frequency: f64) -> Result<Self, BiquadError> {
+        // LR2 squares a first order filter, which is a second order section with a Q of 1/2
+        let (sections, q) = match order {
+            2 => (1, 0.5),
+            4 => (2, std::f64::consts::FRAC_1_SQRT_2),
+            _ => return Err(BiquadError::UnsupportedOrder),
+        };
+        let band = |filter_type| {
+            let mut stage = Biquad::default();
+            stage.prepare(sample_rate)?;
+            stage.set(filter_type, frequency, 0., q)?;
+            Ok::<_, BiquadError>(Cascade::new(vec![stage; sections]))
+        };
+        Ok(Self {
+            low: band(FilterType::Lowpass)?,
+            high: band(FilterType::Highpass)?,
+            order,
+            frequency,
+        })
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    /// The lowpass band.
+    pub fn low(&self) -> &Cascade<F> {
+        &self.low
+    }
+
+    /// The highpass band, without the polarity inversion of LR2.
+    pub fn high(&self) -> &Cascade<F> {
+        &self.high
+    }
+
+    /// Whether the high band has to be inverted to sum flat with the low band, which is the case
+    /// for LR2. The outputs of `tick` and `process` are already inverted.
+    pub fn requires_polarity_invert(&self) -> bool {
+        self.order == 2
+    }
+
+    /// Returns the low and the high band of the sample.
+    #[inline]
+    pub fn tick(&mut self, input: F) -> (F, F) {
+        let low = self
+            .low
+            .stages_mut()
+            .iter_mut()
+            .fold(input, |sample, stage| stage.tick(sample));
+        let high = self
+            .high
+            .stages_mut()
+            .iter_mut()
+            .fold(input, |sample, stage| stage.tick(sample));
+        if self.requires_polarity_invert() {
+            (low, -high)
+        } else {
+            (low, high)
+        }
+    }
+
+    /// Splits `input` into `low` and `high`, up to the shortest length of the three buffers.
+    pub fn process(&mut self, input: &[F], low: &mut [F], high: &mut [F]) {
+        for ((x, low), high) in input.iter().zip(low.iter_mut()).zip(high.iter_mut()) {
+            (*low, *high) = self.tick(*x);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.low.reset();
+        self.high.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lr4_bands_sum_flat() {
+        // with f32 trigonometry sin² + cos² is only 1 within the precision of f32
+        let tolerance = if cfg!(feature = "fast-coeffs") {
+            1e-4
+        } else {
+            1e-9
+        };
+        let crossover = LinkwitzRiley::<f64>::new(4, 48000, 1000.).unwrap();
+        assert!((crossover.low().magnitude_db(1000.) + 6.02).abs() < 0.01);
+        assert!((crossover.high().magnitude_db(1000.) + 6.02).abs() < 0.01);
+
+        for frequency in [50., 500., 1000., 2000., 10000.] {
+            let response = |band: &Cascade<f64>| {
+                band.stages()
+                    .iter()
+                    .map(|stage| stage.coefficients().response(frequency))
+                    .product::<crate::Complex<f64>>()
+            };
+            let sum = response(crossover.low()) + response(crossover.high());
+            assert!((sum.norm() - 1.).abs() < tolerance);
+        }
+    }
+
+    #[test]
+    fn outputs_sum_flat_at_the_crossover() {
+        for order in [2, 4] {
+            let mut crossover = LinkwitzRiley::<f64>::new(order, 48000, 1000.).unwrap();
+            assert_eq!(crossover.requires_polarity_invert(), order == 2);
+
+            let input = crate::signal::sine(1000., 48000, 4800);
+            let (mut low, mut high) = (vec![0.; 4800], vec![0.; 4800]);
+            crossover.process(&input, &mut low, &mut high);
+            let peak = low[2400..]
+                .iter()
+                .zip(&high[2400..])
+                .map(|(low, high)| (low + high).abs())
+                .fold(0., f64::max);
+            assert!((peak - 1.).abs() < 1e-3, "LR{order}");
+        }
+    }
+
+    #[test]
+    fn unsupported_order() {
+        assert!(matches!(
+            LinkwitzRiley::<f32>::new(3, 48000, 1000.),
+            Err(BiquadError::UnsupportedOrder)
+        ));
+    }
+}
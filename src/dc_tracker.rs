@@ -0,0 +1,73 @@
+use crate::{Biquad, BiquadError, FilterType, DEFAULT_Q};
+use num_traits::Float;
+
+/// A DC blocking highpass that also reports the offset it removes, e.g. for metering.
+///
+/// The estimate is the difference between the input and the output of the highpass. It still
+/// carries some of the signal, which only falls off by 6 dB per octave above the cutoff, so a
+/// lower cutoff gives a steadier estimate but takes longer to settle.
+#[derive(Debug, Clone)]
+pub struct DcTracker<F: Float> {
+    highpass: Biquad<F>,
+    estimate: F,
+}
+
+impl<F: Float> DcTracker<F> {
+    pub fn new(sample_rate: u32, cutoff: f64) -> Result<Self, BiquadError> {
+        let mut highpass = Biquad::default();
+        highpass.prepare(sample_rate)?;
+        highpass.set(FilterType::Highpass, cutoff, 0., DEFAULT_Q)?;
+        Ok(Self {
+            highpass,
+            estimate: F::zero(),
+        })
+    }
+
+    /// Returns the input without its DC offset.
+    #[inline]
+    pub fn tick(&mut self, input: F) -> F {
+        let output = self.highpass.tick(input);
+        self.estimate = input - output;
+        output
+    }
+
+    pub fn process_in_place(&mut self, buffer: &mut [F]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.tick(*sample);
+        }
+    }
+
+    /// The DC offset removed from the last sample.
+    pub fn last_dc_estimate(&self) -> F {
+        self.estimate
+    }
+
+    pub fn reset(&mut self) {
+        self.highpass.reset();
+        self.estimate = F::zero();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal;
+
+    #[test]
+    fn estimate_converges_to_offset() {
+        let mut tracker = DcTracker::<f64>::new(48000, 10.).unwrap();
+        let mut buffer: Vec<f64> = signal::sine(1000., 48000, 48000)
+            .iter()
+            .map(|x| 0.5 * x + 0.3)
+            .collect();
+        tracker.process_in_place(&mut buffer);
+
+        // the sine leaks in at about 0.5 · √2 · 10 Hz / 1 kHz
+        assert!((tracker.last_dc_estimate() - 0.3).abs() < 0.01);
+        let mean = buffer[24000..].iter().sum::<f64>() / 24000.;
+        assert!(mean.abs() < 1e-3);
+
+        tracker.reset();
+        assert_eq!(tracker.last_dc_estimate(), 0.);
+    }
+}
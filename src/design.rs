@@ -0,0 +1,256 @@
+use crate::{Biquad, BiquadError, Coefficients, FilterType};
+use num_traits::Float;
+use std::f64::consts::PI;
+
+/// How far inside the unit circle `Biquad::is_stable` wants the squared pole radius, in epsilons.
+pub(crate) const STABILITY_MARGIN: f64 = 4.;
+
+/// Suggests the Q for a notch, so that it attenuates at least `target_attenuation_db` over a
+/// band of `bandwidth` Hz around `center`.
+///
+/// This is the highest Q that meets the target at both edges of the band, which keeps the notch
+/// as narrow as possible. A higher attenuation or a wider band leads to a lower Q. The band has
+/// to have a positive width and lie between 0 Hz and Nyquist: a zero width fails with
+/// `BiquadError::InvalidBandwidth`, edges outside the range with `BiquadError::FrequencyTooLow`
+/// or `BiquadError::FrequencyOverNyqist`. The attenuation has to be finite and not 0 dB, which
+/// even an infinitely narrow notch meets, or it fails with `BiquadError::GainOutOfRange`. Its
+/// sign is ignored.
+pub fn suggest_notch_q(
+    center: f64,
+    bandwidth: f64,
+    target_attenuation_db: f64,
+    sample_rate: u32,
+) -> Result<f64, BiquadError> {
+    if sample_rate == 0 {
+        return Err(BiquadError::InvalidSampleRate);
+    }
+    if bandwidth.is_nan() || bandwidth <= 0. {
+        return Err(BiquadError::InvalidBandwidth);
+    }
+    if !target_attenuation_db.is_finite() || target_attenuation_db == 0. {
+        return Err(BiquadError::GainOutOfRange);
+    }
+    let (low, high) = (center - bandwidth / 2., center + bandwidth / 2.);
+    if low <= 0. {
+        return Err(BiquadError::FrequencyTooLow);
+    }
+    if 2. * high >= sample_rate as f64 {
+        return Err(BiquadError::FrequencyOverNyqist);
+    }
+
+    let warp = |frequency: f64| f64::tan(PI * frequency / sample_rate as f64);
+    let gain = f64::powf(10., -target_attenuation_db.abs() / 10.);
+
+    // solve the power response (1 - w²)² / ((1 - w²)² + w² / Q²) of the prototype for Q
+    let edge_q = |edge: f64| {
+        let w = warp(edge) / warp(center);
+        w / ((1. - w * w).abs() * f64::sqrt(1. / gain - 1.))
+    };
+    Ok(edge_q(low).min(edge_q(high)))
+}
+
+/// The highest Q at `frequency` for which `Biquad::<F>::is_stable` still holds.
+///
+/// Higher Q moves the poles towards the unit circle until the squared pole radius `a2` rounds to
+/// within a few epsilons of 1 in `F`. This applies to all filter types except peaks, whose poles
+/// depend on the gain as well and get closer to the unit circle for cuts. The frequency is
+/// checked and the filter designed as by `Coefficients::set`, so the limit is much lower close to
+/// DC, where `a1` rounds towards 2 as well. If no Q is stable it fails with
+/// `BiquadError::Unstable`.
+pub fn max_stable_q<F: Float>(frequency: f64, sample_rate: u32) -> Result<f64, BiquadError> {
+    let mut filter = Biquad::<F>::default();
+    filter.coefficients.set_sample_rate(sample_rate)?;
+    let omega = filter.coefficients.omega(frequency)?;
+    let mut is_stable = |q: f64| {
+        filter
+            .coefficients
+            .set(FilterType::Bandpass2, frequency, 0., q)
+            .is_ok()
+            && filter.is_stable()
+    };
+
+    // the Q for which a2 is exactly at the margin, before rounding to F
+    let epsilon = F::epsilon().to_f64().unwrap_or(f64::EPSILON);
+    let a2 = 1. - STABILITY_MARGIN * epsilon;
+    let estimate = f64::sin(omega) * (1. + a2) / (2. * (1. - a2));
+
+    // rounding moves the actual limit by up to half the spacing of F around a2, so bracket it and
+    // bisect on the stability check itself
+    let (mut low, mut high) = (estimate, estimate);
+    while !is_stable(low) {
+        low /= 2.;
+        if low < f64::EPSILON {
+            return Err(BiquadError::Unstable);
+        }
+    }
+    while is_stable(high) {
+        high *= 2.;
+    }
+    for _ in 0..f64::MANTISSA_DIGITS {
+        let mid = (low + high) / 2.;
+        if is_stable(mid) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+impl<F: Float> Coefficients<F> {
+    /// An allpass whose group delay peaks at `frequency`, to fill in a dip in the group delay of
+    /// a filter chain it's added to.
+    ///
+    /// The poles sit at the angle of `frequency`, with the radius of a cookbook allpass of the same
+    /// Q. Higher Q gives a narrower and higher peak, of about `2·Q / (π·frequency)` seconds.
+    pub fn allpass_for_delay_correction(
+        frequency: f64,
+        q: f64,
+        sample_rate: u32,
+    ) -> Result<Self, BiquadError> {
+        let mut coefficients = Self::default();
+        coefficients.set_sample_rate(sample_rate)?;
+        let omega = coefficients.omega(frequency)?;
+        if q <= 0. {
+            return Err(BiquadError::NegativeQ);
+        }
+
+        let alpha = f64::sin(omega) / (2. * q);
+        let radius = f64::sqrt((1. - alpha) / (1. + alpha));
+        let (a1, a2) = (-2. * radius * f64::cos(omega), radius * radius);
+        coefficients.store(a2, a1, 1., 1., a1, a2)?;
+        Ok(coefficients)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cascade, FilterSettings};
+
+    #[test]
+    fn suggested_notch_meets_target() {
+        let q = suggest_notch_q(50., 2., 30., 48000).unwrap();
+
+        let filter = Biquad::<f64>::new_precise(48000, FilterType::Notch, 50., 0., q).unwrap();
+        let coefficients = filter.coefficients();
+        assert!(coefficients.magnitude_db(49.) <= -30. + 1e-6);
+        assert!(coefficients.magnitude_db(51.) <= -30. + 1e-6);
+        assert!(
+            coefficients
+                .magnitude_db(49.)
+                .max(coefficients.magnitude_db(51.))
+                > -30.1
+        );
+    }
+
+    #[test]
+    fn max_stable_q_is_the_stability_limit() {
+        let q = max_stable_q::<f32>(1000., 48000).unwrap();
+
+        let mut filter = Biquad::<f32>::default();
+        filter.prepare(48000).unwrap();
+        filter.set(FilterType::Bandpass2, 1000., 0., q).unwrap();
+        assert!(filter.is_stable());
+        filter
+            .set(FilterType::Bandpass2, 1000., 0., 1.01 * q)
+            .unwrap();
+        assert!(!filter.is_stable());
+
+        assert!(max_stable_q::<f64>(1000., 48000).unwrap() > 1e6 * q);
+    }
+
+    #[test]
+    fn max_stable_q_rejects_invalid_frequencies() {
+        assert!(matches!(
+            max_stable_q::<f32>(1000., 0),
+            Err(BiquadError::InvalidSampleRate)
+        ));
+        assert!(matches!(
+            max_stable_q::<f32>(30000., 48000),
+            Err(BiquadError::FrequencyOverNyqist)
+        ));
+        assert!(matches!(
+            max_stable_q::<f32>(0.5, 48000),
+            Err(BiquadError::FrequencyTooLow)
+        ));
+    }
+
+    #[test]
+    fn suggested_notch_q_tracks_narrowness() {
+        let narrow = suggest_notch_q(60., 1., 40., 48000).unwrap();
+        let wide = suggest_notch_q(60., 10., 20., 48000).unwrap();
+        assert!(narrow > wide);
+
+        let deep = suggest_notch_q(60., 2., 40., 48000).unwrap();
+        let shallow = suggest_notch_q(60., 2., 20., 48000).unwrap();
+        assert!(shallow > deep);
+    }
+
+    #[test]
+    fn suggested_notch_q_rejects_invalid_bands() {
+        let check = |center: f64, bandwidth: f64| suggest_notch_q(center, bandwidth, 30., 48000);
+        assert!(matches!(check(60., 0.), Err(BiquadError::InvalidBandwidth)));
+        assert!(matches!(
+            check(60., -2.),
+            Err(BiquadError::InvalidBandwidth)
+        ));
+        assert!(matches!(
+            check(60., f64::NAN),
+            Err(BiquadError::InvalidBandwidth)
+        ));
+        assert!(matches!(
+            check(60., 120.),
+            Err(BiquadError::FrequencyTooLow)
+        ));
+        assert!(matches!(
+            check(23990., 20.),
+            Err(BiquadError::FrequencyOverNyqist)
+        ));
+        assert!(matches!(
+            suggest_notch_q(60., 2., 30., 0),
+            Err(BiquadError::InvalidSampleRate)
+        ));
+        for attenuation in [0., f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                suggest_notch_q(60., 2., attenuation, 48000),
+                Err(BiquadError::GainOutOfRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn allpass_flattens_group_delay_of_a_cut() {
+        let cut = FilterSettings {
+            filter_type: FilterType::Peak,
+            frequency: 2000.,
+            gain_db: -12.,
+            q: 2.,
+        };
+        let mut chain = Cascade::<f64>::from_settings(&[cut], 48000).unwrap();
+        let variation = |chain: &Cascade<f64>| {
+            let delays: Vec<f64> = (0..=100)
+                .map(|i| chain.group_delay(500. * f64::powf(16., i as f64 / 100.)))
+                .collect();
+            let max = delays.iter().cloned().fold(f64::MIN, f64::max);
+            max - delays.iter().cloned().fold(f64::MAX, f64::min)
+        };
+        let before = variation(&chain);
+
+        let allpass = Coefficients::allpass_for_delay_correction(2000., 1.5, 48000).unwrap();
+        let peak = (1900..2100)
+            .map(|f| (f, allpass.group_delay(f as f64)))
+            .fold(
+                (0, 0.),
+                |max, delay| if delay.1 > max.1 { delay } else { max },
+            );
+        assert!((peak.0 as f64 - 2000.).abs() < 20.);
+
+        let mut stage = Biquad::default();
+        stage.set_coefficients(allpass);
+        let mut stages = chain.stages().to_vec();
+        stages.push(stage);
+        chain = Cascade::new(stages);
+        assert!(variation(&chain) < 0.7 * before);
+    }
+}
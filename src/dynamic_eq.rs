@@ -0,0 +1,162 @@
+use crate::{Biquad, BiquadError, FilterType, Intermediates};
+use num_traits::Float;
+
+/// How fast the detector envelope rises and falls, in seconds.
+const ATTACK: f64 = 0.001;
+const RELEASE: f64 = 0.1;
+
+/// A peak band of a dynamic EQ, which turns down when the signal in the band gets loud.
+///
+/// A bandpass at the band picks up the level, which a peak follower smooths. Above the
+/// threshold the peak cuts the band like a compressor with the given ratio: with a ratio of 4,
+/// a band 12 dB over the threshold is cut by 9 dB. Below the threshold the band is flat.
+#[derive(Debug, Clone)]
+pub struct DynamicEqBand<F: Float> {
+    detector: Biquad<F>,
+    peak: Biquad<F>,
+    intermediates: Option<Intermediates>,
+    q: f64,
+    threshold_db: f64,
+    ratio: f64,
+    attack: f64,
+    release: f64,
+    envelope: f64,
+    gain_db: f64,
+}
+
+impl<F: Float> DynamicEqBand<F> {
+    /// Creates the band, which passes everything unchanged until it is `set`.
+    pub fn new(sample_rate: u32) -> Result<Self, BiquadError> {
+        let mut detector = Biquad::default();
+        detector.prepare(sample_rate)?;
+        let smoothing = |time: f64| 1. - f64::exp(-1. / (time * sample_rate as f64));
+        Ok(Self {
+            peak: detector.clone(),
+            detector,
+            intermediates: None,
+            q: 1.,
+            threshold_db: 0.,
+            ratio: 1.,
+            attack: smoothing(ATTACK),
+            release: smoothing(RELEASE),
+            envelope: 0.,
+            gain_db: 0.,
+        })
+    }
+
+    /// Moves the band to `frequency` and sets the threshold in dBFS and the ratio at which it
+    /// is cut. Ratios below 1 are treated as 1, which never cuts.
+    pub fn set(
+        &mut self,
+        frequency: f64,
+        q: f64,
+        threshold_db: f64,
+        ratio: f64,
+    ) -> Result<(), BiquadError> {
+        self.detector.set(FilterType::Bandpass2, frequency, 0., q)?;
+        let mut peak = self.peak.coefficients();
+        peak.set(FilterType::Peak, frequency, self.gain_db, q)?;
+        let intermediates =
+            peak.intermediates_with_trig(frequency, 0., q, cfg!(feature = "fast-coeffs"))?;
+        self.peak.set_coefficients(peak);
+        self.intermediates = Some(intermediates);
+        self.q = q;
+        self.threshold_db = threshold_db;
+        self.ratio = ratio.max(1.);
+        Ok(())
+    }
+
+    /// The current gain of the band in dB, 0 or below.
+    pub fn gain_db(&self) -> f64 {
+        self.gain_db
+    }
+
+    #[inline]
+    pub fn tick(&mut self, input: F) -> F {
+        let Some(mut intermediates) = self.intermediates else {
+            return input;
+        };
+        let level = self.detector.tick(input).to_f64().unwrap_or(0.).abs();
+        let smoothing = if level > self.envelope {
+            self.attack
+        } else {
+            self.release
+        };
+        self.envelope += smoothing * (level - self.envelope);
+
+        let over_db = 20. * f64::log10(self.envelope) - self.threshold_db;
+        let gain_db = if over_db > 0. {
+            over_db / self.ratio - over_db
+        } else {
+            0.
+        };
+        if gain_db != self.gain_db {
+            intermediates.a = f64::powf(10., gain_db / 40.);
+            // coefficients that can't be stored leave the previous ones in place
+            if self
+                .peak
+                .coefficients
+                .store_cookbook(FilterType::Peak, self.q, &intermediates)
+                .is_ok()
+            {
+                self.gain_db = gain_db;
+            }
+        }
+        self.peak.tick(input)
+    }
+
+    pub fn process_in_place(&mut self, buffer: &mut [F]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.tick(*sample);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.detector.reset();
+        self.peak.reset();
+        self.envelope = 0.;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal;
+
+    fn rms(signal: &[f64]) -> f64 {
+        (signal.iter().map(|x| x * x).sum::<f64>() / signal.len() as f64).sqrt()
+    }
+
+    fn output_gain_db(band: &mut DynamicEqBand<f64>, amplitude: f64) -> f64 {
+        let input: Vec<f64> = signal::sine(1000., 48000, 48000)
+            .iter()
+            .map(|x| amplitude * x)
+            .collect();
+        let mut output = input.clone();
+        band.process_in_place(&mut output);
+        20. * f64::log10(rms(&output[24000..]) / rms(&input[24000..]))
+    }
+
+    #[test]
+    fn loud_tone_is_cut() {
+        let mut band = DynamicEqBand::<f64>::new(48000).unwrap();
+        band.set(1000., 2., -20., 4.).unwrap();
+
+        // 20 dB over the threshold is cut by 15 dB
+        let loud = output_gain_db(&mut band, 1.);
+        assert!((band.gain_db() + 15.).abs() < 0.5);
+        assert!((loud + 15.).abs() < 0.5);
+
+        band.reset();
+        let quiet = output_gain_db(&mut band, 0.01);
+        assert!(quiet.abs() < 0.01);
+    }
+
+    #[test]
+    fn unset_band_passes() {
+        let mut band = DynamicEqBand::<f32>::new(48000).unwrap();
+        let mut buffer = [1., -0.5, 0.25];
+        band.process_in_place(&mut buffer);
+        assert_eq!(buffer, [1., -0.5, 0.25]);
+    }
+}
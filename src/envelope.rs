@@ -0,0 +1,84 @@
+use crate::Coefficients;
+use num_traits::Float;
+
+/// Tracks the maximum magnitude response seen over successive coefficient snapshots,
+/// e.g. to show the worst case boost of an automated filter.
+///
+/// The response is sampled at logarithmically spaced frequencies.
+#[derive(Debug, Clone)]
+pub struct ResponseEnvelope {
+    frequencies: Vec<f64>,
+    max_db: Vec<f64>,
+}
+
+impl ResponseEnvelope {
+    pub fn new(min_frequency: f64, max_frequency: f64, bins: usize) -> Self {
+        let ratio = max_frequency / min_frequency;
+        let frequencies = (0..bins)
+            .map(|bin| min_frequency * ratio.powf(bin as f64 / (bins.max(2) - 1) as f64))
+            .collect();
+        Self {
+            frequencies,
+            max_db: vec![f64::NEG_INFINITY; bins],
+        }
+    }
+
+    /// Adds the response of a snapshot to the envelope.
+    pub fn add<F: Float>(&mut self, coefficients: &Coefficients<F>) {
+        self.max_db
+            .iter_mut()
+            .zip(&self.frequencies)
+            .for_each(|(max_db, frequency)| {
+                *max_db = max_db.max(coefficients.magnitude_db(*frequency));
+            });
+    }
+
+    /// The maximum magnitude in dB seen so far in the bin closest to `frequency`.
+    ///
+    /// Returns negative infinity if nothing was added yet.
+    pub fn max_db(&self, frequency: f64) -> f64 {
+        let distance = |f: f64| (f.ln() - frequency.ln()).abs();
+        self.frequencies
+            .iter()
+            .zip(&self.max_db)
+            .min_by(|(a, _), (b, _)| distance(**a).total_cmp(&distance(**b)))
+            .map_or(f64::NEG_INFINITY, |(_, max_db)| *max_db)
+    }
+
+    /// The center frequencies of the bins.
+    pub fn frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+
+    pub fn reset(&mut self) {
+        self.max_db.fill(f64::NEG_INFINITY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FilterType;
+
+    #[test]
+    fn envelope_is_per_bin_max() {
+        let mut low = Coefficients::<f32>::default();
+        low.set_sample_rate(48000).unwrap();
+        low.set(FilterType::Peak, 100., 6., 1.).unwrap();
+        let mut high = low.clone();
+        high.set(FilterType::Peak, 5000., -3., 2.).unwrap();
+
+        let mut envelope = ResponseEnvelope::new(20., 20000., 64);
+        assert_eq!(envelope.max_db(1000.), f64::NEG_INFINITY);
+        envelope.add(&low);
+        envelope.add(&high);
+
+        for frequency in envelope.frequencies() {
+            let expected = low
+                .magnitude_db(*frequency)
+                .max(high.magnitude_db(*frequency));
+            assert_eq!(envelope.max_db(*frequency), expected);
+        }
+        assert!((envelope.max_db(100.) - 6.).abs() < 0.5);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{Biquad, BiquadError, Cascade, FilterType};
+use num_traits::Float;
+
+/// The three lowest formants of a vowel as `(frequency, bandwidth)` in Hz, the averages of
+/// adult male speakers from Peterson and Barney.
+const FORMANTS: [[(f64, f64); 3]; 5] = [
+    [(730., 90.), (1090., 110.), (2440., 170.)],
+    [(530., 60.), (1840., 100.), (2480., 170.)],
+    [(270., 50.), (2290., 100.), (3010., 180.)],
+    [(570., 70.), (840., 80.), (2410., 170.)],
+    [(300., 50.), (870., 80.), (2240., 150.)],
+];
+
+/// The boost of each formant, the higher ones are weaker in speech.
+const GAINS_DB: [f64; 3] = [12., 9., 6.];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Vowel {
+    A,
+    E,
+    I,
+    O,
+    U,
+}
+
+impl Vowel {
+    /// The formant frequencies and bandwidths in Hz.
+    pub fn formants(&self) -> [(f64, f64); 3] {
+        FORMANTS[*self as usize]
+    }
+}
+
+/// Peaks at the formants of a vowel in series, to shape a source into a vowel sound.
+#[derive(Debug, Clone)]
+pub struct FormantFilter<F: Float> {
+    cascade: Cascade<F>,
+    vowel: Vowel,
+}
+
+impl<F: Float> FormantFilter<F> {
+    pub fn new(sample_rate: u32, vowel: Vowel) -> Result<Self, BiquadError> {
+        let mut stage = Biquad::default();
+        stage.prepare(sample_rate)?;
+        let mut filter = Self {
+            cascade: Cascade::new(vec![stage; GAINS_DB.len()]),
+            vowel,
+        };
+        filter.set_vowel(vowel)?;
+        Ok(filter)
+    }
+
+    /// Moves the peaks to the formants of `vowel`, keeping the state for smooth transitions.
+    pub fn set_vowel(&mut self, vowel: Vowel) -> Result<(), BiquadError> {
+        let stages = self.cascade.stages_mut();
+        for ((stage, (frequency, bandwidth)), gain_db) in
+            stages.iter_mut().zip(vowel.formants()).zip(GAINS_DB)
+        {
+            stage.set(FilterType::Peak, frequency, gain_db, frequency / bandwidth)?;
+        }
+        self.vowel = vowel;
+        Ok(())
+    }
+
+    pub fn vowel(&self) -> Vowel {
+        self.vowel
+    }
+
+    pub fn cascade(&self) -> &Cascade<F> {
+        &self.cascade
+    }
+
+    pub fn process_in_place(&mut self, buffer: &mut [F]) {
+        self.cascade.process_in_place(buffer);
+    }
+
+    pub fn reset(&mut self) {
+        self.cascade.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vowel_a_peaks_at_its_formants() {
+        let filter = FormantFilter::<f64>::new(48000, Vowel::A).unwrap();
+        let response: Vec<(f64, f64)> = (10..400)
+            .map(|i| {
+                let frequency = i as f64 * 10.;
+                (frequency, filter.cascade().magnitude_db(frequency))
+            })
+            .collect();
+        let peaks: Vec<f64> = response
+            .windows(3)
+            .filter(|w| w[1].1 > w[0].1 && w[1].1 > w[2].1)
+            .map(|w| w[1].0)
+            .collect();
+
+        assert_eq!(peaks.len(), 3);
+        for (peak, expected) in peaks.iter().zip([730., 1090., 2440.]) {
+            assert!((peak - expected).abs() / expected < 0.05);
+        }
+    }
+
+    #[test]
+    fn set_vowel_moves_the_peaks() {
+        let mut filter = FormantFilter::<f32>::new(48000, Vowel::A).unwrap();
+        filter.set_vowel(Vowel::I).unwrap();
+        assert_eq!(filter.vowel(), Vowel::I);
+        assert!(filter.cascade().magnitude_db(270.) > 11.);
+        assert!(filter.cascade().magnitude_db(730.) < 3.);
+    }
+}
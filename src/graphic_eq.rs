@@ -0,0 +1,133 @@
+use crate::{Biquad, BiquadError, Cascade, FilterType};
+use num_traits::Float;
+
+const OCTAVE_CENTERS: [f64; 10] = [
+    31.5, 63., 125., 250., 500., 1000., 2000., 4000., 8000., 16000.,
+];
+const THIRD_OCTAVE_CENTERS: [f64; 30] = [
+    25., 31.5, 40., 50., 63., 80., 100., 125., 160., 200., 250., 315., 400., 500., 630., 800.,
+    1000., 1250., 1600., 2000., 2500., 3150., 4000., 5000., 6300., 8000., 10000., 12500., 16000.,
+    20000.,
+];
+
+/// The ISO center frequencies of the bands of a graphic EQ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IsoBands {
+    /// 10 bands from 31.5 Hz to 16 kHz.
+    Octave,
+    /// 30 bands from 25 Hz to 20 kHz.
+    ThirdOctave,
+}
+
+impl IsoBands {
+    pub fn centers(&self) -> &'static [f64] {
+        match self {
+            IsoBands::Octave => &OCTAVE_CENTERS,
+            IsoBands::ThirdOctave => &THIRD_OCTAVE_CENTERS,
+        }
+    }
+
+    /// The Q of a peaking filter that is as wide as one band.
+    pub fn q(&self) -> f64 {
+        let octaves: f64 = match self {
+            IsoBands::Octave => 1.,
+            IsoBands::ThirdOctave => 1. / 3.,
+        };
+        f64::sqrt(octaves.exp2()) / (octaves.exp2() - 1.)
+    }
+}
+
+/// A graphic EQ of peaking filters at the ISO band centers, all flat initially.
+#[derive(Debug, Clone)]
+pub struct GraphicEq<F: Float> {
+    bands: IsoBands,
+    gains_db: Vec<f64>,
+    cascade: Cascade<F>,
+}
+
+impl<F: Float> GraphicEq<F> {
+    /// Creates the EQ, leaving out the bands that don't fit below Nyquist.
+    pub fn new_iso(bands: IsoBands, sample_rate: u32) -> Result<Self, BiquadError> {
+        let stages = bands
+            .centers()
+            .iter()
+            .filter(|center| 2. * **center < sample_rate as f64)
+            .map(|center| {
+                let mut stage = Biquad::default();
+                stage.prepare(sample_rate)?;
+                stage.set(FilterType::Peak, *center, 0., bands.q())?;
+                Ok(stage)
+            })
+            .collect::<Result<Vec<_>, BiquadError>>()?;
+        Ok(Self {
+            bands,
+            gains_db: vec![0.; stages.len()],
+            cascade: Cascade::new(stages),
+        })
+    }
+
+    pub fn set_band_gain(&mut self, index: usize, gain_db: f64) -> Result<(), BiquadError> {
+        let stage = self
+            .cascade
+            .stages_mut()
+            .get_mut(index)
+            .ok_or(BiquadError::InvalidBand)?;
+        stage.set(
+            FilterType::Peak,
+            self.bands.centers()[index],
+            gain_db,
+            self.bands.q(),
+        )?;
+        self.gains_db[index] = gain_db;
+        Ok(())
+    }
+
+    pub fn band_gain(&self, index: usize) -> Option<f64> {
+        self.gains_db.get(index).copied()
+    }
+
+    /// The center frequencies of the bands in use.
+    pub fn frequencies(&self) -> &[f64] {
+        &self.bands.centers()[..self.gains_db.len()]
+    }
+
+    pub fn cascade(&self) -> &Cascade<F> {
+        &self.cascade
+    }
+
+    pub fn process_in_place(&mut self, buffer: &mut [F]) {
+        self.cascade.process_in_place(buffer);
+    }
+
+    pub fn reset(&mut self) {
+        self.cascade.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn band_gain_shows_at_iso_frequency() {
+        let mut eq = GraphicEq::<f32>::new_iso(IsoBands::Octave, 48000).unwrap();
+        assert_eq!(eq.frequencies().len(), 10);
+
+        let index = eq.frequencies().iter().position(|f| *f == 1000.).unwrap();
+        eq.set_band_gain(index, 6.).unwrap();
+        assert_eq!(eq.band_gain(index), Some(6.));
+        assert!((eq.cascade().magnitude_db(1000.) - 6.).abs() < 0.01);
+        assert!(eq.cascade().magnitude_db(125.).abs() < 0.1);
+
+        assert!(matches!(
+            eq.set_band_gain(10, 3.),
+            Err(BiquadError::InvalidBand)
+        ));
+    }
+
+    #[test]
+    fn bands_above_nyquist_are_left_out() {
+        let eq = GraphicEq::<f32>::new_iso(IsoBands::ThirdOctave, 32000).unwrap();
+        assert_eq!(eq.frequencies().last(), Some(&12500.));
+    }
+}
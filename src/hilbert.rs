@@ -0,0 +1,104 @@
+use crate::{Biquad, BiquadError, Cascade, Coefficients};
+use num_traits::Float;
+
+/// `a²` of the allpass sections `(a² - z⁻²) / (1 - a²·z⁻²)` of both paths, by Olli Niemitalo.
+const IN_PHASE: [f64; 4] = [
+    0.161_758_498_367_7,
+    0.733_028_932_341_5,
+    0.945_349_700_329_1,
+    0.990_599_156_684_5,
+];
+const QUADRATURE: [f64; 4] = [
+    0.479_400_865_588_8,
+    0.876_218_493_539_3,
+    0.976_597_589_508_2,
+    0.997_499_255_935_5,
+];
+
+/// Two parallel allpass cascades whose outputs are about 90° apart, an approximation of the
+/// Hilbert transform for envelope detection and frequency shifting.
+///
+/// The phase difference stays within 0.7° of 90° from about 0.0005 to 0.4995 of the sample
+/// rate, e.g. 22 Hz to 22 kHz at 44.1 kHz. Both outputs have unit gain but a frequency dependent
+/// phase relative to the input, only their difference is constant.
+#[derive(Debug, Clone)]
+pub struct HilbertBiquad<F: Float> {
+    in_phase: Cascade<F>,
+    quadrature: Cascade<F>,
+    /// the quadrature path has an extra sample of delay
+    delayed: F,
+}
+
+impl<F: Float> HilbertBiquad<F> {
+    pub fn new(sample_rate: u32) -> Result<Self, BiquadError> {
+        let cascade = |sections: &[f64]| {
+            sections
+                .iter()
+                .map(|a2| {
+                    let mut coefficients = Coefficients::default();
+                    coefficients.set_sample_rate(sample_rate)?;
+                    coefficients.store(*a2, 0., -1., 1., 0., -a2)?;
+                    let mut stage = Biquad::default();
+                    stage.set_coefficients(coefficients);
+                    Ok(stage)
+                })
+                .collect::<Result<Vec<_>, BiquadError>>()
+                .map(Cascade::new)
+        };
+        Ok(Self {
+            in_phase: cascade(&IN_PHASE)?,
+            quadrature: cascade(&QUADRATURE)?,
+            delayed: F::zero(),
+        })
+    }
+
+    /// Returns the in-phase and the quadrature signal, which lags the in-phase signal by 90°.
+    #[inline]
+    pub fn tick(&mut self, input: F) -> (F, F) {
+        let in_phase = self
+            .in_phase
+            .stages_mut()
+            .iter_mut()
+            .fold(input, |sample, stage| stage.tick(sample));
+        let quadrature = self
+            .quadrature
+            .stages_mut()
+            .iter_mut()
+            .fold(input, |sample, stage| stage.tick(sample));
+        let delayed = std::mem::replace(&mut self.delayed, quadrature);
+        (in_phase, delayed)
+    }
+
+    pub fn reset(&mut self) {
+        self.in_phase.reset();
+        self.quadrature.reset();
+        self.delayed = F::zero();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal;
+
+    #[test]
+    fn outputs_are_in_quadrature() {
+        let input = signal::sine(1000., 44100, 8192);
+        let mut hilbert = HilbertBiquad::<f64>::new(44100).unwrap();
+        let (in_phase, quadrature): (Vec<f64>, Vec<f64>) =
+            input.iter().map(|x| hilbert.tick(*x)).unzip();
+
+        // after the transient, the normalized correlation of the outputs is cos(Δφ)
+        let (i, q) = (&in_phase[4096..8000], &quadrature[4096..8000]);
+        let power = |signal: &[f64]| signal.iter().map(|x| x * x).sum::<f64>();
+        let correlation: f64 = i.iter().zip(q).map(|(i, q)| i * q).sum();
+        let cos = correlation / f64::sqrt(power(i) * power(q));
+        assert!(cos.abs() < f64::sin(1f64.to_radians()));
+        assert!((power(i) / power(q) - 1.).abs() < 1e-3);
+
+        // a quadrature signal lagging by 90° correlates with the in-phase signal delayed by a
+        // quarter period, which is 11 samples at 1 kHz
+        let delayed: f64 = in_phase[4085..7989].iter().zip(q).map(|(i, q)| i * q).sum();
+        assert!(delayed > 0.9 * f64::sqrt(power(i) * power(q)));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{BiquadError, Coefficients, FilterType};
+use num_traits::Float;
+
+/// A biquad in normalized lattice form.
+///
+/// Each lattice stage is a rotation, so the energy of the internal state can't grow by itself,
+/// no matter how fast the coefficients change. This makes it the better choice for heavy
+/// modulation, where the direct form can produce large transients. It is more expensive than
+/// `Biquad` though, and the ladder taps grow large for poles very close to the unit circle.
+#[derive(Debug, Clone)]
+pub struct LatticeBiquad<F: Float> {
+    coefficients: Coefficients<F>,
+    /// reflection coefficients and their complements `sqrt(1 - k²)`
+    k1: F,
+    k2: F,
+    c1: F,
+    c2: F,
+    /// ladder taps
+    w0: F,
+    w1: F,
+    w2: F,
+    g0: F,
+    g1: F,
+}
+
+impl<F: Float> Default for LatticeBiquad<F> {
+    fn default() -> Self {
+        Self {
+            coefficients: Coefficients::default(),
+            k1: F::zero(),
+            k2: F::zero(),
+            c1: F::one(),
+            c2: F::one(),
+            w0: F::zero(),
+            w1: F::zero(),
+            w2: F::zero(),
+            g0: F::zero(),
+            g1: F::zero(),
+        }
+    }
+}
+
+impl<F: Float> LatticeBiquad<F> {
+    pub fn prepare(&mut self, sample_rate: u32) -> Result<(), BiquadError> {
+        self.coefficients.set_sample_rate(sample_rate)
+    }
+
+    /// Designs the filter like `Biquad::set` and converts it to the lattice form.
+    ///
+    /// Fails with `BiquadError::Unstable` if the poles are not inside the unit circle,
+    /// which the lattice can't represent.
+    pub fn set(
+        &mut self,
+        filter_type: FilterType,
+        frequency: f64,
+        gain_db: f64,
+        q: f64,
+    ) -> Result<(), BiquadError> {
+        let mut coefficients = self.coefficients.clone();
+        coefficients.set(filter_type, frequency, gain_db, q)?;
+
+        let [b0, b1, b2, a1, a2] = [
+            coefficients.b0,
+            coefficients.b1,
+            coefficients.b2,
+            coefficients.a1,
+            coefficients.a2,
+        ]
+        .map(|c| c.to_f64().unwrap_or(0.));
+        let k2 = a2;
+        let k1 = a1 / (1. + a2);
+        if k1.abs() >= 1. || k2.abs() >= 1. {
+            return Err(BiquadError::Unstable);
+        }
+        let c1 = f64::sqrt(1. - k1 * k1);
+        let c2 = f64::sqrt(1. - k2 * k2);
+
+        // taps of the unnormalized lattice, scaled to the normalized state
+        let v2 = b2;
+        let v1 = b1 - v2 * a1;
+        let v0 = b0 - v2 * a2 - v1 * k1;
+        let convert = |c: f64| F::from(c).ok_or(BiquadError::Fatal);
+
+        self.k1 = convert(k1)?;
+        self.k2 = convert(k2)?;
+        self.c1 = convert(c1)?;
+        self.c2 = convert(c2)?;
+        self.w0 = convert(v0 / (c1 * c2))?;
+        self.w1 = convert(v1 / c2)?;
+        self.w2 = convert(v2)?;
+        self.coefficients = coefficients;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn tick(&mut self, input: F) -> F {
+        let f1 = self.c2 * input - self.k2 * self.g1;
+        let g2 = self.k2 * input + self.c2 * self.g1;
+        let f0 = self.c1 * f1 - self.k1 * self.g0;
+        let g1 = self.k1 * f1 + self.c1 * self.g0;
+
+        self.g0 = f0;
+        self.g1 = g1;
+
+        self.w0 * f0 + self.w1 * g1 + self.w2 * g2
+    }
+
+    pub fn process_in_place(&mut self, buffer: &mut [F]) {
+        buffer
+            .iter_mut()
+            .for_each(|sample| *sample = self.tick(*sample));
+    }
+
+    pub fn reset(&mut self) {
+        self.g0 = F::zero();
+        self.g1 = F::zero();
+    }
+
+    /// The direct form coefficients the lattice was designed from.
+    pub fn coefficients(&self) -> Coefficients<F> {
+        self.coefficients.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signal, Biquad};
+
+    #[test]
+    fn lattice_matches_direct_form() {
+        let input = signal::white_noise(256, 1);
+
+        let mut lattice = LatticeBiquad::<f64>::default();
+        lattice.prepare(48000).unwrap();
+        lattice.set(FilterType::Peak, 1000., 6., 2.).unwrap();
+        let mut direct = Biquad::<f64>::default();
+        direct.prepare(48000).unwrap();
+        direct.set(FilterType::Peak, 1000., 6., 2.).unwrap();
+
+        for x in input {
+            assert!((lattice.tick(x) - direct.tick(x)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn lattice_stays_bounded_under_modulation() {
+        let input = signal::white_noise(4800, 3);
+
+        let mut lattice = LatticeBiquad::<f64>::default();
+        lattice.prepare(48000).unwrap();
+        let mut direct = Biquad::<f64>::default();
+        direct.prepare(48000).unwrap();
+
+        // switching between two stable direct form filters can still make it grow without bound
+        let (mut lattice_peak, mut direct_peak) = (0f64, 0f64);
+        for (n, x) in input.iter().enumerate() {
+            let frequency = if (n / 3) % 2 == 0 { 30. } else { 12000. };
+            lattice.set(FilterType::Lowpass, frequency, 0., 2.).unwrap();
+            direct.set(FilterType::Lowpass, frequency, 0., 2.).unwrap();
+            lattice_peak = lattice_peak.max(lattice.tick(*x).abs());
+            direct_peak = direct_peak.max(direct.tick(*x).abs());
+        }
+        assert!(lattice_peak < 10.);
+        assert!(direct_peak > 1e6);
+    }
+}
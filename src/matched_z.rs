@@ -0,0 +1,181 @@
+use crate::{Biquad, BiquadError, Coefficients, FilterType};
+use num_traits::Float;
+use std::f64::consts::PI;
+
+impl<F: Float> Coefficients<F> {
+    /// Sets the coefficients by mapping the poles and zeros of the analog prototype with the
+    /// matched-Z transform `z = e^(s·T)` instead of the bilinear transform.
+    ///
+    /// Unlike the bilinear transform this keeps the pole and zero frequencies where they are,
+    /// which mostly matters close to Nyquist. In exchange the magnitude response is aliased and
+    /// only matches the analog one at a single reference frequency: DC, except for the highpass
+    /// (Nyquist) and the bandpasses (center frequency). Zeros at infinity are placed at Nyquist.
+    ///
+    /// Custom coefficients are already digital and are set as `set` does.
+    pub fn set_matched_z(
+        &mut self,
+        filter_type: FilterType,
+        frequency: f64,
+        gain_db: f64,
+        q: f64,
+    ) -> Result<(), BiquadError> {
+        if let FilterType::Custom { .. } = filter_type {
+            return self.set(filter_type, frequency, gain_db, q);
+        }
+        let omega = self.omega(frequency)?;
+        if q < 0.0 {
+            return Err(BiquadError::NegativeQ);
+        }
+
+        let (numerator, denominator) =
+            analog_prototype(filter_type, f64::powf(10., gain_db / 40.), q);
+        let b = matched_polynomial(numerator, omega);
+        let a = matched_polynomial(denominator, omega);
+
+        let reference = match filter_type {
+            FilterType::Highpass => PI,
+            FilterType::Bandpass1 | FilterType::Bandpass2 => omega,
+            _ => 0.,
+        };
+        let analog_gain = analog_magnitude(numerator, reference / omega)
+            / analog_magnitude(denominator, reference / omega);
+        let scale = analog_gain * digital_magnitude(a, reference) / digital_magnitude(b, reference);
+
+        self.store(scale * b[0], scale * b[1], scale * b[2], a[0], a[1], a[2])
+    }
+}
+
+impl<F: Float> Biquad<F> {
+    /// Sets the coefficients with the matched-Z transform, see `Coefficients::set_matched_z`.
+    ///
+    /// The settings are cleared, as they describe a bilinear design that methods like
+    /// `transpose` or `reconfigure` would recompute the filter from.
+    pub fn set_matched_z(
+        &mut self,
+        filter_type: FilterType,
+        frequency: f64,
+        gain_db: f64,
+        q: f64,
+    ) -> Result<(), BiquadError> {
+        self.coefficients
+            .set_matched_z(filter_type, frequency, gain_db, q)?;
+        self.settings = None;
+        Ok(())
+    }
+}
+
+/// Numerator and denominator `[c0, c1, c2]` of `c0 + c1·s + c2·s²` with the design frequency at 1 rad/s.
+fn analog_prototype(filter_type: FilterType, a: f64, q: f64) -> ([f64; 3], [f64; 3]) {
+    let resonant = [1., 1. / q, 1.];
+    match filter_type {
+        FilterType::Lowpass => ([1., 0., 0.], resonant),
+        FilterType::Highpass => ([0., 0., 1.], resonant),
+        FilterType::Bandpass1 => ([0., 1., 0.], resonant),
+        FilterType::Bandpass2 => ([0., 1. / q, 0.], resonant),
+        FilterType::Notch => ([1., 0., 1.], resonant),
+        FilterType::Allpass => ([1., -1. / q, 1.], resonant),
+        FilterType::Peak => ([1., a / q, 1.], [1., 1. / (a * q), 1.]),
+        FilterType::Lowshelf => ([a * a, a * a.sqrt() / q, a], [1., a.sqrt() / q, a]),
+        FilterType::Highshelf => ([a, a * a.sqrt() / q, a * a], [a, a.sqrt() / q, 1.]),
+        FilterType::Custom { .. } => unreachable!("custom coefficients have no analog prototype"),
+    }
+}
+
+/// Maps the roots of an analog polynomial to `1 + d1·z⁻¹ + d2·z⁻²`.
+fn matched_polynomial([c0, c1, c2]: [f64; 3], omega: f64) -> [f64; 3] {
+    if c2 == 0. && c1 == 0. {
+        // both zeros at infinity
+        return [1., 2., 1.];
+    }
+    if c2 == 0. {
+        // one root at infinity, the other one real
+        let z = f64::exp(-c0 / c1 * omega);
+        return [1., 1. - z, -z];
+    }
+
+    let sigma = -c1 / (2. * c2);
+    let discriminant = c1 * c1 - 4. * c2 * c0;
+    if discriminant < 0. {
+        let radius = f64::exp(sigma * omega);
+        let angle = f64::sqrt(-discriminant) / (2. * c2) * omega;
+        [1., -2. * radius * f64::cos(angle), radius * radius]
+    } else {
+        let spread = f64::sqrt(discriminant) / (2. * c2);
+        let z1 = f64::exp((sigma + spread) * omega);
+        let z2 = f64::exp((sigma - spread) * omega);
+        [1., -(z1 + z2), z1 * z2]
+    }
+}
+
+fn analog_magnitude([c0, c1, c2]: [f64; 3], w: f64) -> f64 {
+    f64::hypot(c0 - c2 * w * w, c1 * w)
+}
+
+fn digital_magnitude([d0, d1, d2]: [f64; 3], w: f64) -> f64 {
+    f64::hypot(
+        d0 + d1 * f64::cos(w) + d2 * f64::cos(2. * w),
+        d1 * f64::sin(w) + d2 * f64::sin(2. * w),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The frequency of the pole angle, which is where the filter resonates.
+    fn resonance(coefficients: &Coefficients<f64>) -> f64 {
+        let radius = coefficients.a2.sqrt();
+        let angle = f64::acos(-coefficients.a1 / (2. * radius));
+        angle * coefficients.sample_rate / (2. * PI)
+    }
+
+    #[test]
+    fn matched_z_keeps_resonance_location() {
+        let mut bilinear = Coefficients::<f64>::default();
+        bilinear.set_sample_rate(44100).unwrap();
+        bilinear.set(FilterType::Lowpass, 16000., 0., 4.).unwrap();
+
+        let mut matched = bilinear.clone();
+        matched
+            .set_matched_z(FilterType::Lowpass, 16000., 0., 4.)
+            .unwrap();
+
+        // the damped analog poles oscillate slightly below the design frequency
+        let analog = 16000. * f64::sqrt(1. - 1. / (4. * 4. * 4.));
+        assert!((resonance(&matched) - analog).abs() < 1.);
+        assert!((resonance(&bilinear) - analog).abs() > 50.);
+    }
+
+    #[test]
+    fn matched_z_matches_reference_gain() {
+        let mut coefficients = Coefficients::<f64>::default();
+        coefficients.set_sample_rate(48000).unwrap();
+
+        coefficients
+            .set_matched_z(FilterType::Lowpass, 1000., 0., 0.707)
+            .unwrap();
+        assert!(coefficients.magnitude_db(1.).abs() < 0.01);
+
+        coefficients
+            .set_matched_z(FilterType::Peak, 1000., 6., 1.)
+            .unwrap();
+        assert!(coefficients.magnitude_db(1.).abs() < 0.01);
+        assert!((coefficients.magnitude_db(1000.) - 6.).abs() < 0.1);
+    }
+
+    #[test]
+    fn biquad_drops_bilinear_settings() {
+        let mut filter = Biquad::<f64>::default();
+        filter.prepare(48000).unwrap();
+        filter.set(FilterType::Lowpass, 1000., 0., 0.707).unwrap();
+        filter
+            .set_matched_z(FilterType::Lowpass, 15000., 0., 0.707)
+            .unwrap();
+        assert_eq!(filter.settings(), None);
+
+        let matched = filter.coefficients();
+        filter.reconfigure(48000).unwrap();
+        assert_eq!(filter.coefficients(), matched);
+        assert!(matches!(filter.transpose(2.), Err(BiquadError::NoSettings)));
+    }
+}
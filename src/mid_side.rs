@@ -0,0 +1,126 @@
+use crate::{Biquad, BiquadError};
+use num_traits::Float;
+
+/// Filters the mid and side channels of a stereo signal separately, as in mastering EQs.
+///
+/// The mid channel is `(L + R) / 2` and the side channel `(L - R) / 2`. With the same settings
+/// on both filters this is the same as filtering left and right.
+#[derive(Debug, Clone)]
+pub struct MidSideBiquad<F: Float> {
+    mid: Biquad<F>,
+    side: Biquad<F>,
+}
+
+impl<F: Float> MidSideBiquad<F> {
+    /// Creates the filter with both channels passing unchanged.
+    pub fn new(sample_rate: u32) -> Result<Self, BiquadError> {
+        let mut mid = Biquad::default();
+        mid.prepare(sample_rate)?;
+        mid.coefficients.store(1., 0., 0., 1., 0., 0.)?;
+        Ok(Self {
+            side: mid.clone(),
+            mid,
+        })
+    }
+
+    pub fn mid(&self) -> &Biquad<F> {
+        &self.mid
+    }
+
+    pub fn mid_mut(&mut self) -> &mut Biquad<F> {
+        &mut self.mid
+    }
+
+    pub fn side(&self) -> &Biquad<F> {
+        &self.side
+    }
+
+    pub fn side_mut(&mut self) -> &mut Biquad<F> {
+        &mut self.side
+    }
+
+    /// Filters interleaved stereo audio `[L, R, L, R, ...]` in place.
+    ///
+    /// # Panics
+    ///
+    /// If the buffer has an odd length.
+    pub fn process_interleaved(&mut self, buffer: &mut [F]) {
+        assert!(
+            buffer.len().is_multiple_of(2),
+            "got {} samples of interleaved stereo",
+            buffer.len()
+        );
+        let half = (F::one() + F::one()).recip();
+        for frame in buffer.chunks_exact_mut(2) {
+            let mid = self.mid.tick((frame[0] + frame[1]) * half);
+            let side = self.side.tick((frame[0] - frame[1]) * half);
+            frame[0] = mid + side;
+            frame[1] = mid - side;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.mid.reset();
+        self.side.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signal, FilterType};
+
+    fn interleave(left: &[f64], right: &[f64]) -> Vec<f64> {
+        left.iter().zip(right).flat_map(|(l, r)| [*l, *r]).collect()
+    }
+
+    #[test]
+    fn mid_boost_leaves_side_signal() {
+        let mut filter = MidSideBiquad::<f64>::new(48000).unwrap();
+        filter
+            .mid_mut()
+            .set(FilterType::Peak, 1000., 9., 1.)
+            .unwrap();
+        let left = signal::white_noise(1024, 5);
+        let right: Vec<f64> = left.iter().map(|x| -x).collect();
+        let mut buffer = interleave(&left, &right);
+        let expected = buffer.clone();
+        filter.process_interleaved(&mut buffer);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn new_filter_passes_unchanged() {
+        let mut filter = MidSideBiquad::<f64>::new(48000).unwrap();
+        let mut buffer = vec![0.5, 0.25, -1., 0.75];
+        filter.process_interleaved(&mut buffer);
+        assert_eq!(buffer, [0.5, 0.25, -1., 0.75]);
+    }
+
+    #[test]
+    fn linked_filters_equal_stereo_eq() {
+        let mut filter = MidSideBiquad::<f64>::new(48000).unwrap();
+        filter
+            .mid_mut()
+            .set(FilterType::Lowshelf, 200., -4., 0.7)
+            .unwrap();
+        filter
+            .side_mut()
+            .set(FilterType::Lowshelf, 200., -4., 0.7)
+            .unwrap();
+        let left = signal::white_noise(1024, 5);
+        let right = signal::sine(440., 48000, 1024);
+        let mut buffer = interleave(&left, &right);
+        filter.process_interleaved(&mut buffer);
+
+        let mut stereo = filter.mid().clone();
+        stereo.reset();
+        let expected = interleave(
+            &stereo.clone().process_to_vec(&left),
+            &stereo.process_to_vec(&right),
+        );
+        for (y, expected) in buffer.iter().zip(&expected) {
+            assert!((y - expected).abs() < 1e-12);
+        }
+    }
+}
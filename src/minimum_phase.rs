@@ -0,0 +1,73 @@
+use crate::{Coefficients, Complex};
+use num_traits::Float;
+
+impl<F: Float> Coefficients<F> {
+    /// The filter with the same magnitude response and the least phase shift and delay.
+    ///
+    /// Zeros outside the unit circle are reflected to their conjugate reciprocals inside it and
+    /// the gain is scaled by their radius, which leaves the magnitude unchanged on the unit
+    /// circle. Leading zero coefficients of the numerator, a pure delay, are dropped. The poles
+    /// are kept as they are, so an unstable filter stays unstable.
+    pub fn to_minimum_phase(&self) -> Coefficients<F> {
+        let [b0, b1, b2, ..] = self.as_f64();
+        let [mut gain, c1, c2] = match [b0, b1, b2] {
+            [0., 0., b2] => [b2, 0., 0.],
+            [0., b1, b2] => [b1, b2, 0.],
+            numerator => numerator,
+        };
+        if gain == 0. {
+            return self.clone();
+        }
+
+        // the zeros of z² + c1·z + c2, for a first order numerator one of them is at 0
+        let (c1, c2) = (c1 / gain, c2 / gain);
+        let root = Complex::new(c1 * c1 - 4. * c2, 0.).sqrt();
+        let zeros = [(-root - c1) / 2., (root - c1) / 2.].map(|zero| {
+            let radius = zero.norm();
+            if radius > 1. {
+                gain *= radius;
+                zero.conj().inv()
+            } else {
+                zero
+            }
+        });
+
+        let mut minimum = self.clone();
+        let convert = |c: f64| F::from(c).unwrap_or_else(F::zero);
+        minimum.b0 = convert(gain);
+        minimum.b1 = convert(-gain * (zeros[0] + zeros[1]).re);
+        minimum.b2 = convert(gain * (zeros[0] * zeros[1]).re);
+        minimum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FilterType;
+
+    #[test]
+    fn reflects_zeros_into_the_unit_circle() {
+        let mut minimum = Coefficients::<f64>::default();
+        minimum.set_sample_rate(48000).unwrap();
+        minimum.set(FilterType::Peak, 1000., 9., 2.).unwrap();
+
+        // the reversed numerator has the reflected zeros and the same magnitude
+        let mut mixed = minimum.clone();
+        (mixed.b0, mixed.b2) = (minimum.b2, minimum.b0);
+        let restored = mixed.to_minimum_phase();
+        assert!(restored.approx_eq(&minimum, 1e-12));
+        for frequency in [100., 700., 1000., 1500., 10000.] {
+            assert!(
+                (restored.magnitude_db(frequency) - mixed.magnitude_db(frequency)).abs() < 1e-9
+            );
+            assert!(restored.group_delay(frequency) < mixed.group_delay(frequency));
+        }
+        assert!(minimum.to_minimum_phase().approx_eq(&minimum, 1e-12));
+
+        let mut delay = minimum.clone();
+        [delay.b0, delay.b1, delay.b2, delay.a1, delay.a2] = [0., 0., 0.5, 0., 0.];
+        let gain = delay.to_minimum_phase();
+        assert_eq!([gain.b0, gain.b1, gain.b2], [0.5, 0., 0.]);
+    }
+}
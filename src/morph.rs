@@ -0,0 +1,207 @@
+use crate::{Coefficients, Complex};
+use num_traits::Float;
+
+/// Roots of `z² + c1·z + c2`, a conjugate pair is represented by the root above the real axis.
+#[derive(Debug, Clone, Copy)]
+enum Roots {
+    /// `radius·e^(±i·angle)`
+    Conjugate {
+        radius: f64,
+        angle: f64,
+    },
+    Real(f64, f64),
+}
+
+impl Roots {
+    fn new(c1: f64, c2: f64) -> Self {
+        let discriminant = c1 * c1 - 4. * c2;
+        if discriminant < 0. {
+            let radius = c2.sqrt();
+            Roots::Conjugate {
+                radius,
+                angle: f64::acos((-c1 / (2. * radius)).clamp(-1., 1.)),
+            }
+        } else {
+            let root = discriminant.sqrt();
+            Roots::Real((-c1 - root) / 2., (-c1 + root) / 2.)
+        }
+    }
+
+    /// Interpolates the roots and returns `[c1, c2]` of the polynomial they belong to.
+    ///
+    /// Conjugate pairs interpolate their angle and their decay `-ln(radius)` geometrically where
+    /// possible, which moves resonances along a log frequency axis with a nearly constant Q.
+    /// Pairs of different kinds fall back to interpolating the coefficients.
+    fn interpolate(self, other: Roots, t: f64, fallback: [f64; 2]) -> [f64; 2] {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        let geometric = |a: f64, b: f64| {
+            if a > 0. && b > 0. {
+                a.powf(1. - t) * b.powf(t)
+            } else {
+                lerp(a, b)
+            }
+        };
+        match (self, other) {
+            (
+                Roots::Conjugate { radius, angle },
+                Roots::Conjugate {
+                    radius: other_radius,
+                    angle: other_angle,
+                },
+            ) => {
+                let radius = if radius > 0. && other_radius > 0. {
+                    f64::exp(-geometric(-radius.ln(), -other_radius.ln()))
+                } else {
+                    lerp(radius, other_radius)
+                };
+                let angle = geometric(angle, other_angle);
+                [-2. * radius * angle.cos(), radius * radius]
+            }
+            (Roots::Real(p1, p2), Roots::Real(q1, q2)) => {
+                let (r1, r2) = (lerp(p1, q1), lerp(p2, q2));
+                [-(r1 + r2), r1 * r2]
+            }
+            _ => fallback,
+        }
+    }
+}
+
+impl<F: Float> Coefficients<F> {
+    /// Morphs towards `other` by interpolating poles and zeros instead of coefficients, `t` runs
+    /// from 0 for `self` to 1 for `other`.
+    ///
+    /// Interpolating the coefficients directly moves the poles on strange paths: between two
+    /// lowpasses it adds a resonance that neither of them has. Here the pole and zero pairs move
+    /// separately, resonances keep their Q and glide on a log frequency axis. The gain is
+    /// interpolated in dB at DC, or at Nyquist for filters that block DC, or else at the
+    /// frequency of the poles. Numerators without a `z⁰` term are interpolated directly.
+    pub fn morph_poles_zeros(&self, other: &Coefficients<F>, t: f64) -> Coefficients<F> {
+        let [b0, b1, b2, a0, a1, a2] = self.as_f64();
+        let [ob0, ob1, ob2, oa0, oa1, oa2] = other.as_f64();
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+
+        let [d1, d2] = Roots::new(a1 / a0, a2 / a0).interpolate(
+            Roots::new(oa1 / oa0, oa2 / oa0),
+            t,
+            [lerp(a1 / a0, oa1 / oa0), lerp(a2 / a0, oa2 / oa0)],
+        );
+        let denominator = [1., d1, d2];
+
+        let numerator = if b0 == 0. || ob0 == 0. {
+            [
+                lerp(b0 / a0, ob0 / oa0),
+                lerp(b1 / a0, ob1 / oa0),
+                lerp(b2 / a0, ob2 / oa0),
+            ]
+        } else {
+            let [n1, n2] = Roots::new(b1 / b0, b2 / b0).interpolate(
+                Roots::new(ob1 / ob0, ob2 / ob0),
+                t,
+                [lerp(b1 / b0, ob1 / ob0), lerp(b2 / b0, ob2 / ob0)],
+            );
+            let shape = |z: Complex<f64>| evaluate([1., n1, n2], z) / evaluate(denominator, z);
+            let pole_angle = match Roots::new(d1, d2) {
+                Roots::Conjugate { angle, .. } => angle,
+                Roots::Real(..) => std::f64::consts::FRAC_PI_2,
+            };
+            let reference = [
+                Complex::new(1., 0.),
+                Complex::new(-1., 0.),
+                Complex::from_polar(1., pole_angle),
+            ]
+            .into_iter()
+            .find(|z| {
+                [self.eval_z(*z), other.eval_z(*z), shape(*z)]
+                    .iter()
+                    .all(|response| response.norm() > 1e-9)
+            });
+            let gain = match reference {
+                Some(z) => {
+                    let target =
+                        self.eval_z(z).norm().powf(1. - t) * other.eval_z(z).norm().powf(t);
+                    target / shape(z).norm() * lerp(b0 / a0, ob0 / oa0).signum()
+                }
+                None => lerp(b0 / a0, ob0 / oa0),
+            };
+            [gain, gain * n1, gain * n2]
+        };
+
+        let convert = |c: f64| F::from(c).unwrap_or_else(F::zero);
+        Coefficients {
+            sample_rate: self.sample_rate,
+            b0: convert(numerator[0]),
+            b1: convert(numerator[1]),
+            b2: convert(numerator[2]),
+            a0: F::one(),
+            a1: convert(denominator[1]),
+            a2: convert(denominator[2]),
+        }
+    }
+}
+
+/// `c0 + c1·z⁻¹ + c2·z⁻²`
+fn evaluate([c0, c1, c2]: [f64; 3], z: Complex<f64>) -> Complex<f64> {
+    let z1 = z.inv();
+    z1 * (z1 * c2 + c1) + c0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FilterType, DEFAULT_Q};
+
+    fn lowpass(frequency: f64) -> Coefficients<f64> {
+        let mut coefficients = Coefficients::default();
+        coefficients.set_sample_rate(48000).unwrap();
+        coefficients
+            .set(FilterType::Lowpass, frequency, 0., DEFAULT_Q)
+            .unwrap();
+        coefficients
+    }
+
+    fn peak_db(coefficients: &Coefficients<f64>) -> f64 {
+        (1..2400)
+            .map(|i| coefficients.magnitude_db(i as f64 * 10.))
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    #[test]
+    fn morphed_lowpass_stays_a_lowpass() {
+        let (from, to) = (lowpass(200.), lowpass(8000.));
+        let mut previous = 0.;
+        for i in 1..10 {
+            let t = i as f64 / 10.;
+            let morphed = from.morph_poles_zeros(&to, t);
+            assert!(morphed.magnitude_db(10.).abs() < 0.01);
+            assert!(peak_db(&morphed) < 0.1);
+            assert!(morphed.magnitude_db(23990.) < -40.);
+            assert!((morphed.effective_q() - DEFAULT_Q).abs() < 0.1);
+
+            // the cutoff rises steadily
+            let cutoff = (1..2400)
+                .map(|i| i as f64 * 10.)
+                .find(|f| morphed.magnitude_db(*f) < -3.)
+                .unwrap();
+            assert!(cutoff > previous);
+            previous = cutoff;
+        }
+        assert!(from
+            .morph_poles_zeros(&to, 0.)
+            .response_approx_eq(&from, 1e-6));
+        assert!(from
+            .morph_poles_zeros(&to, 1.)
+            .response_approx_eq(&to, 1e-6));
+
+        // interpolating the coefficients resonates on the way
+        let naive = Coefficients {
+            sample_rate: 48000.,
+            b0: 0.75 * from.b0 + 0.25 * to.b0,
+            b1: 0.75 * from.b1 + 0.25 * to.b1,
+            b2: 0.75 * from.b2 + 0.25 * to.b2,
+            a0: 1.,
+            a1: 0.75 * from.a1 + 0.25 * to.a1,
+            a2: 0.75 * from.a2 + 0.25 * to.a2,
+        };
+        assert!(peak_db(&naive) > 3.);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{Biquad, BiquadError, FilterType};
+use num_traits::Float;
+
+/// One set of coefficients shared by several channels, each with its own state.
+#[derive(Debug, Clone)]
+pub struct MultiBiquad<F: Float> {
+    filter: Biquad<F>,
+    channels: Vec<Biquad<F>>,
+}
+
+impl<F: Float> MultiBiquad<F> {
+    pub fn new(channels: usize) -> Self {
+        Self {
+            filter: Biquad::default(),
+            channels: vec![Biquad::default(); channels],
+        }
+    }
+
+    pub fn prepare(&mut self, sample_rate: u32) -> Result<(), BiquadError> {
+        self.filter.prepare(sample_rate)?;
+        self.update_channels();
+        Ok(())
+    }
+
+    /// Sets the coefficients of all channels, leaving their state untouched.
+    pub fn set(
+        &mut self,
+        filter_type: FilterType,
+        frequency: f64,
+        gain_db: f64,
+        q: f64,
+    ) -> Result<(), BiquadError> {
+        self.filter.set(filter_type, frequency, gain_db, q)?;
+        self.update_channels();
+        Ok(())
+    }
+
+    /// Filters planar audio in place, one buffer per channel.
+    ///
+    /// # Panics
+    ///
+    /// If the number of buffers is not the number of channels.
+    pub fn process_planar(&mut self, channels: &mut [&mut [F]]) {
+        assert_eq!(
+            channels.len(),
+            self.channels.len(),
+            "got {} buffers for a filter with {} channels",
+            channels.len(),
+            self.channels.len()
+        );
+        for (filter, buffer) in self.channels.iter_mut().zip(channels) {
+            filter.process_in_place(buffer);
+        }
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn reset(&mut self) {
+        self.channels.iter_mut().for_each(Biquad::reset);
+    }
+
+    fn update_channels(&mut self) {
+        for channel in &mut self.channels {
+            channel.set_coefficients(self.filter.coefficients());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal;
+
+    #[test]
+    fn planar_channels_are_independent() {
+        let inputs: Vec<Vec<f32>> = (0..4)
+            .map(|seed| {
+                signal::white_noise(100, seed + 1)
+                    .iter()
+                    .map(|x| *x as f32)
+                    .collect()
+            })
+            .collect();
+
+        let mut multi = MultiBiquad::<f32>::new(4);
+        multi.prepare(48000).unwrap();
+        multi.set(FilterType::Lowpass, 2000., 0., 0.707).unwrap();
+        let mut outputs = inputs.clone();
+        let mut buffers: Vec<&mut [f32]> = outputs.iter_mut().map(Vec::as_mut_slice).collect();
+        multi.process_planar(&mut buffers);
+
+        for (input, output) in inputs.iter().zip(&outputs) {
+            let mut filter = Biquad::<f32>::default();
+            filter.prepare(48000).unwrap();
+            filter.set(FilterType::Lowpass, 2000., 0., 0.707).unwrap();
+            assert_eq!(&filter.process_to_vec(input), output);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "got 3 buffers for a filter with 4 channels")]
+    fn planar_channel_count_must_match() {
+        let mut multi = MultiBiquad::<f32>::new(4);
+        let mut buffers = [[0f32; 8]; 3];
+        let mut buffers: Vec<&mut [f32]> = buffers.iter_mut().map(|b| b.as_mut_slice()).collect();
+        multi.process_planar(&mut buffers);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{Biquad, BiquadError, FilterType, LinkwitzRiley};
+use num_traits::Float;
+use std::f64::consts::FRAC_1_SQRT_2;
+
+/// Splits a signal into `BANDS` frequency bands with successive LR4 crossovers, so the bands
+/// sum to an allpass version of the input.
+///
+/// Each crossover splits the highpass band of the one before. The lower bands then miss the
+/// phase shift that the later crossovers apply to the upper bands, which would make the sum
+/// dip. So each of them also runs through the allpass equivalent of every later crossover.
+#[derive(Debug, Clone)]
+pub struct MultibandSplitter<F: Float, const BANDS: usize> {
+    crossovers: Vec<LinkwitzRiley<F>>,
+    /// for each band, the allpasses of the crossovers above it
+    compensation: Vec<Vec<Biquad<F>>>,
+}
+
+impl<F: Float, const BANDS: usize> MultibandSplitter<F, BANDS> {
+    /// Creates the splitter with the `BANDS - 1` crossover frequencies in ascending order.
+    /// Frequencies that are not ascending fail with `BiquadError::UnsortedCrossovers`.
+    ///
+    /// # Panics
+    ///
+    /// If the number of crossover frequencies is not `BANDS - 1`.
+    pub fn new(sample_rate: u32, crossovers: &[f64]) -> Result<Self, BiquadError> {
+        assert_eq!(
+            crossovers.len() + 1,
+            BANDS,
+            "got {} crossovers for {} bands",
+            crossovers.len(),
+            BANDS
+        );
+        if crossovers.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(BiquadError::UnsortedCrossovers);
+        }
+        let compensation = (0..BANDS)
+            .map(|band| {
+                crossovers
+                    .iter()
+                    .skip(band + 1)
+                    .map(|frequency| {
+                        // the sum of both bands of an LR4 crossover
+                        let mut allpass = Biquad::default();
+                        allpass.prepare(sample_rate)?;
+                        allpass.set(FilterType::Allpass, *frequency, 0., FRAC_1_SQRT_2)?;
+                        Ok(allpass)
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, BiquadError>>()?;
+        Ok(Self {
+            crossovers: crossovers
+                .iter()
+                .map(|frequency| LinkwitzRiley::new(4, sample_rate, *frequency))
+                .collect::<Result<_, _>>()?,
+            compensation,
+        })
+    }
+
+    /// Splits `input` into the bands, from the lowest to the highest. Up to the shortest length
+    /// of the buffers is processed.
+    ///
+    /// # Panics
+    ///
+    /// If the number of band buffers is not `BANDS`.
+    pub fn split(&mut self, input: &[F], bands: &mut [&mut [F]]) {
+        assert_eq!(
+            bands.len(),
+            BANDS,
+            "got {} buffers for {} bands",
+            bands.len(),
+            BANDS
+        );
+        let len = bands
+            .iter()
+            .map(|band| band.len())
+            .fold(input.len(), usize::min);
+        let Some((rest, lower)) = bands.split_last_mut() else {
+            return;
+        };
+        rest[..len].copy_from_slice(&input[..len]);
+        for (crossover, band) in self.crossovers.iter_mut().zip(lower.iter_mut()) {
+            for (rest, low) in rest[..len].iter_mut().zip(band.iter_mut()) {
+                (*low, *rest) = crossover.tick(*rest);
+            }
+        }
+        for (allpasses, band) in self.compensation.iter_mut().zip(bands.iter_mut()) {
+            for allpass in allpasses {
+                allpass.process_in_place(&mut band[..len]);
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.crossovers.iter_mut().for_each(LinkwitzRiley::reset);
+        self.compensation
+            .iter_mut()
+            .flatten()
+            .for_each(Biquad::reset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn three_bands_sum_flat() {
+        // fast-coeffs designs the crossovers and allpasses with f32 trigonometry
+        let tolerance = if cfg!(feature = "fast-coeffs") {
+            1e-3
+        } else {
+            1e-6
+        };
+        let mut splitter = MultibandSplitter::<f64, 3>::new(48000, &[300., 3000.]).unwrap();
+        let len = 16384;
+        let impulse = signal::impulse(len);
+        let mut bands = vec![vec![0.; len]; 3];
+        {
+            let mut buffers: Vec<&mut [f64]> = bands.iter_mut().map(Vec::as_mut_slice).collect();
+            splitter.split(&impulse, &mut buffers);
+        }
+        let sum: Vec<f64> = (0..len)
+            .map(|n| bands.iter().map(|band| band[n]).sum())
+            .collect();
+
+        // the magnitude of the DFT of the summed impulse response
+        for frequency in [30., 300., 1000., 3000., 10000.] {
+            let omega = 2. * PI * frequency / 48000.;
+            let (re, im) = sum.iter().enumerate().fold((0., 0.), |(re, im), (n, h)| {
+                (
+                    re + h * f64::cos(omega * n as f64),
+                    im - h * f64::sin(omega * n as f64),
+                )
+            });
+            assert!((f64::hypot(re, im) - 1.).abs() < tolerance, "{frequency}");
+        }
+
+        // each band carries its own range
+        let energy = |band: &[f64]| band.iter().map(|x| x * x).sum::<f64>();
+        let low = signal::sine(100., 48000, len);
+        let mut buffers = vec![vec![0.; len]; 3];
+        splitter.reset();
+        {
+            let mut slices: Vec<&mut [f64]> = buffers.iter_mut().map(Vec::as_mut_slice).collect();
+            splitter.split(&low, &mut slices);
+        }
+        assert!(energy(&buffers[0]) > 100. * energy(&buffers[1]));
+        assert!(energy(&buffers[0]) > 1000. * energy(&buffers[2]));
+    }
+
+    #[test]
+    fn unsorted_crossovers() {
+        assert!(matches!(
+            MultibandSplitter::<f32, 3>::new(48000, &[3000., 300.]),
+            Err(BiquadError::UnsortedCrossovers)
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{Biquad, BiquadError, FilterType};
+use num_traits::Float;
+
+/// The MIDI note at which the cutoff is the base cutoff, middle C.
+const REFERENCE_NOTE: u8 = 60;
+
+/// A filter whose cutoff follows the played note, as in a synth voice.
+#[derive(Debug, Clone)]
+pub struct NoteTrackedFilter<F: Float> {
+    filter: Biquad<F>,
+    filter_type: FilterType,
+    base_cutoff: f64,
+    q: f64,
+    tracking: f64,
+    cutoff: f64,
+}
+
+impl<F: Float> NoteTrackedFilter<F> {
+    /// Creates the filter at `base_cutoff`, which is where it stays for middle C (note 60).
+    ///
+    /// `tracking_percent` is how far the cutoff follows the note: at 100 % it moves an octave for
+    /// every octave of the note, at 0 % it stays at the base cutoff.
+    pub fn new(
+        sample_rate: u32,
+        filter_type: FilterType,
+        base_cutoff: f64,
+        q: f64,
+        tracking_percent: f64,
+    ) -> Result<Self, BiquadError> {
+        let mut filter = Biquad::default();
+        filter.prepare(sample_rate)?;
+        filter.set(filter_type, base_cutoff, 0., q)?;
+        Ok(Self {
+            filter,
+            filter_type,
+            base_cutoff,
+            q,
+            tracking: tracking_percent / 100.,
+            cutoff: base_cutoff,
+        })
+    }
+
+    /// Retunes the filter to the note. The state is kept, so a legato note doesn't click.
+    pub fn note_on(&mut self, note: u8) -> Result<(), BiquadError> {
+        let semitones = note as f64 - REFERENCE_NOTE as f64;
+        let cutoff = self.base_cutoff * f64::exp2(self.tracking * semitones / 12.);
+        self.filter.set(self.filter_type, cutoff, 0., self.q)?;
+        self.cutoff = cutoff;
+        Ok(())
+    }
+
+    /// The cutoff for the last note.
+    pub fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+
+    #[inline]
+    pub fn tick(&mut self, input: F) -> F {
+        self.filter.tick(input)
+    }
+
+    pub fn reset(&mut self) {
+        self.filter.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_tracking_follows_octaves() {
+        let mut filter =
+            NoteTrackedFilter::<f64>::new(48000, FilterType::Lowpass, 1000., 0.707, 100.).unwrap();
+        filter.note_on(60).unwrap();
+        assert_eq!(filter.cutoff(), 1000.);
+        filter.note_on(72).unwrap();
+        assert!((filter.cutoff() - 2000.).abs() < 1e-9);
+
+        let mut expected = Biquad::<f64>::default();
+        expected.prepare(48000).unwrap();
+        expected.set(FilterType::Lowpass, 2000., 0., 0.707).unwrap();
+        assert!(filter
+            .filter
+            .coefficients()
+            .response_approx_eq(&expected.coefficients(), 1e-9));
+
+        let mut half =
+            NoteTrackedFilter::<f64>::new(48000, FilterType::Lowpass, 1000., 0.707, 50.).unwrap();
+        half.note_on(84).unwrap();
+        assert!((half.cutoff() - 2000.).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{Biquad, BiquadError, Coefficients};
+use num_traits::Float;
+use std::f64::consts::PI;
+
+impl<F: Float> Coefficients<F> {
+    /// Sets a peak that keeps the gain and bandwidth of the analog peak close to Nyquist.
+    ///
+    /// The bilinear transform forces the gain of `set(FilterType::Peak, ..)` to 0 dB at Nyquist,
+    /// which cramps high bands: they get narrower and asymmetric. This is the design of Orfanidis,
+    /// "Digital Parametric Equalizer Design With Prescribed Nyquist-Frequency Gain", which gives
+    /// Nyquist the gain of the analog peak instead. The bandwidth is `frequency / q` between
+    /// the frequencies with half of `gain_db`, as for the cookbook peak, and has to fit below
+    /// Nyquist to match the analog peak. Far below Nyquist both designs are nearly the same.
+    pub fn set_peak_matched(
+        &mut self,
+        frequency: f64,
+        gain_db: f64,
+        q: f64,
+    ) -> Result<(), BiquadError> {
+        let w0 = self.omega(frequency)?;
+        if q <= 0.0 {
+            return Err(BiquadError::NegativeQ);
+        }
+        if gain_db == 0. {
+            return self.store(1., 0., 0., 1., 0., 0.);
+        }
+
+        let g = f64::powf(10., gain_db / 20.);
+        // the reference gain is 1 and the band edges have the geometric mean of both gains
+        let (g0, gb) = (1., g.sqrt());
+        let dw = w0 / q;
+
+        let f = (g * g - gb * gb).abs();
+        let g00 = (g * g - g0 * g0).abs();
+        let f00 = (gb * gb - g0 * g0).abs();
+
+        // the gain of the analog peak at Nyquist
+        let detuning = (w0 * w0 - PI * PI).powi(2);
+        let g1 = f64::sqrt(
+            (g0 * g0 * detuning + g * g * f00 * PI * PI * dw * dw / f)
+                / (detuning + f00 * PI * PI * dw * dw / f),
+        );
+
+        let g01 = (g * g - g0 * g1).abs();
+        let g11 = (g * g - g1 * g1).abs();
+        let f01 = (gb * gb - g0 * g1).abs();
+        let f11 = (gb * gb - g1 * g1).abs();
+
+        let w2 = f64::sqrt(g11 / g00) * f64::tan(w0 / 2.).powi(2);
+        let bandwidth = (1. + f64::sqrt(f00 / f11) * w2) * f64::tan(dw / 2.);
+        let c = f11 * bandwidth * bandwidth - 2. * w2 * (f01 - f64::sqrt(f00 * f11));
+        let d = 2. * w2 * (g01 - f64::sqrt(g00 * g11));
+        let a = f64::sqrt((c + d) / f);
+        let b = f64::sqrt((g * g * c + gb * gb * d) / f);
+
+        self.store(
+            g1 + g0 * w2 + b,
+            -2. * (g1 - g0 * w2),
+            g1 - b + g0 * w2,
+            1. + w2 + a,
+            -2. * (1. - w2),
+            1. + w2 - a,
+        )
+    }
+}
+
+impl<F: Float> Biquad<F> {
+    pub fn set_peak_matched(
+        &mut self,
+        frequency: f64,
+        gain_db: f64,
+        q: f64,
+    ) -> Result<(), BiquadError> {
+        self.coefficients.set_peak_matched(frequency, gain_db, q)?;
+        self.settings = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FilterType;
+
+    /// The magnitude of the analog peak in dB.
+    fn analog_db(frequency: f64, center: f64, gain_db: f64, q: f64) -> f64 {
+        let a = f64::powf(10., gain_db / 40.);
+        let w = frequency / center;
+        let magnitude = |damping: f64| f64::hypot(1. - w * w, damping * w);
+        20. * f64::log10(magnitude(a / q) / magnitude(1. / (a * q)))
+    }
+
+    #[test]
+    fn matched_peak_keeps_shape_near_nyquist() {
+        let mut cookbook = Coefficients::<f64>::default();
+        cookbook.set_sample_rate(44100).unwrap();
+        let mut matched = cookbook.clone();
+        cookbook.set(FilterType::Peak, 15000., 6., 2.).unwrap();
+        matched.set_peak_matched(15000., 6., 2.).unwrap();
+
+        assert!((matched.magnitude_db(15000.) - 6.).abs() < 0.01);
+        assert!((matched.magnitude_db(22050.) - analog_db(22050., 15000., 6., 2.)).abs() < 0.01);
+
+        let error = |coefficients: &Coefficients<f64>| {
+            [12000., 18000., 20000., 22000.]
+                .iter()
+                .map(|f| (coefficients.magnitude_db(*f) - analog_db(*f, 15000., 6., 2.)).abs())
+                .fold(0., f64::max)
+        };
+        assert!(error(&matched) < 0.5);
+        assert!(error(&cookbook) > 2.);
+    }
+
+    #[test]
+    fn matched_peak_is_close_to_cookbook_at_low_frequencies() {
+        let mut cookbook = Coefficients::<f64>::default();
+        cookbook.set_sample_rate(48000).unwrap();
+        let mut matched = cookbook.clone();
+        cookbook.set(FilterType::Peak, 200., -9., 2.).unwrap();
+        matched.set_peak_matched(200., -9., 2.).unwrap();
+        assert!(matched.response_approx_eq(&cookbook, 0.05));
+    }
+}
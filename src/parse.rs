@@ -0,0 +1,125 @@
+use crate::{BiquadError, FilterSettings, FilterType, DEFAULT_Q};
+use std::str::FromStr;
+
+impl FromStr for FilterSettings {
+    type Err = BiquadError;
+
+    /// Parses a compact spec like `"lowpass 1000 q0.7"` or `"peak 1k +3db q1.2"`.
+    ///
+    /// The filter type comes first, then the frequency in Hz with an optional `k` for kHz and an
+    /// optional `hz`. The gain in dB (with an optional `db`) and the Q (prefixed with `q`) may
+    /// follow in any order and default to 0 dB and a Butterworth Q. Case is ignored. This only
+    /// parses, the values are checked when a filter is set from the settings.
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = |token: &str| BiquadError::InvalidSpec(token.to_string());
+        let spec = spec.to_lowercase();
+        let mut tokens = spec.split_whitespace();
+
+        let name = tokens.next().ok_or_else(|| invalid(""))?;
+        let filter_type = match name {
+            "lowpass" | "lp" => FilterType::Lowpass,
+            "highpass" | "hp" => FilterType::Highpass,
+            "bandpass1" => FilterType::Bandpass1,
+            "bandpass2" | "bandpass" | "bp" => FilterType::Bandpass2,
+            "notch" => FilterType::Notch,
+            "allpass" | "ap" => FilterType::Allpass,
+            "peak" | "bell" => FilterType::Peak,
+            "lowshelf" | "ls" => FilterType::Lowshelf,
+            "highshelf" | "hs" => FilterType::Highshelf,
+            _ => return Err(invalid(name)),
+        };
+
+        let token = tokens.next().ok_or_else(|| invalid(&spec))?;
+        let frequency = token.strip_suffix("hz").unwrap_or(token);
+        let frequency = match frequency.strip_suffix('k') {
+            Some(khz) => number(khz).map(|khz| khz * 1000.),
+            None => number(frequency),
+        }
+        .ok_or_else(|| invalid(token))?;
+
+        let (mut gain_db, mut q) = (None, None);
+        for token in tokens {
+            let (value, parsed) = match token.strip_prefix('q') {
+                Some(value) => (&mut q, number(value)),
+                None => (
+                    &mut gain_db,
+                    number(token.strip_suffix("db").unwrap_or(token)),
+                ),
+            };
+            match (value.is_none(), parsed) {
+                (true, Some(parsed)) => *value = Some(parsed),
+                _ => return Err(invalid(token)),
+            }
+        }
+
+        Ok(FilterSettings {
+            filter_type,
+            frequency,
+            gain_db: gain_db.unwrap_or(0.),
+            q: q.unwrap_or(DEFAULT_Q),
+        })
+    }
+}
+
+/// A finite number, a leading `+` is accepted for gains like `+3`.
+fn number(token: &str) -> Option<f64> {
+    f64::from_str(token).ok().filter(|value| value.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(spec: &str) -> Result<FilterSettings, BiquadError> {
+        spec.parse()
+    }
+
+    #[test]
+    fn parses_valid_specs() {
+        let settings = parse("lowpass 1000 q0.7").unwrap();
+        assert_eq!(
+            settings,
+            FilterSettings {
+                filter_type: FilterType::Lowpass,
+                frequency: 1000.,
+                gain_db: 0.,
+                q: 0.7,
+            }
+        );
+
+        let settings = parse("peak 1k +3db q1.2").unwrap();
+        assert_eq!(settings.filter_type, FilterType::Peak);
+        assert_eq!(settings.frequency, 1000.);
+        assert_eq!(settings.gain_db, 3.);
+        assert_eq!(settings.q, 1.2);
+
+        let settings = parse("  HighShelf 2.5kHz -6  ").unwrap();
+        assert_eq!(settings.filter_type, FilterType::Highshelf);
+        assert_eq!(settings.frequency, 2500.);
+        assert_eq!(settings.gain_db, -6.);
+        assert_eq!(settings.q, DEFAULT_Q);
+
+        let settings = parse("notch 60hz q10").unwrap();
+        assert_eq!((settings.frequency, settings.q), (60., 10.));
+    }
+
+    #[test]
+    fn rejects_invalid_specs() {
+        for spec in [
+            "",
+            "lowpass",
+            "wobble 1000",
+            "lowpass fast",
+            "lowpass 1000 q",
+            "peak 1k +3 +4",
+            "peak 1k q1 q2",
+            "peak 1k 3dbb",
+            "lowpass inf",
+        ] {
+            assert!(
+                matches!(parse(spec), Err(BiquadError::InvalidSpec(_))),
+                "{spec}"
+            );
+        }
+    }
+}
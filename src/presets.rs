@@ -0,0 +1,61 @@
+//! Starting points for common mixing tasks.
+
+use crate::{Biquad, BiquadError, FilterType, DEFAULT_Q};
+use num_traits::Float;
+
+fn preset<F: Float>(
+    sample_rate: u32,
+    filter_type: FilterType,
+    frequency: f64,
+    gain_db: f64,
+    q: f64,
+) -> Result<Biquad<F>, BiquadError> {
+    let mut filter = Biquad::default();
+    filter.prepare(sample_rate)?;
+    filter.set(filter_type, frequency, gain_db, q)?;
+    Ok(filter)
+}
+
+/// A Butterworth highpass at 80 Hz, to remove rumble and handling noise below a voice.
+pub fn vocal_hpf<F: Float>(sample_rate: u32) -> Result<Biquad<F>, BiquadError> {
+    preset(sample_rate, FilterType::Highpass, 80., 0., DEFAULT_Q)
+}
+
+/// A gentle high shelf of +3 dB at 12 kHz, to add air.
+pub fn air_shelf<F: Float>(sample_rate: u32) -> Result<Biquad<F>, BiquadError> {
+    preset(sample_rate, FilterType::Highshelf, 12000., 3., DEFAULT_Q)
+}
+
+/// A bandpass around 7 kHz spanning about 6 to 8 kHz, to detect sibilance for a de-esser.
+pub fn deesser_band<F: Float>(sample_rate: u32) -> Result<Biquad<F>, BiquadError> {
+    preset(sample_rate, FilterType::Bandpass2, 6950., 0., 2.8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vocal_hpf_cuts_at_80_hz() {
+        let c = vocal_hpf::<f64>(48000).unwrap().coefficients();
+        assert!((c.magnitude_db(80.) + 3.01).abs() < 0.05);
+        assert!(c.magnitude_db(40.) < -12.);
+    }
+
+    #[test]
+    fn air_shelf_boosts_highs() {
+        let c = air_shelf::<f64>(48000).unwrap().coefficients();
+        assert!((c.magnitude_db(12000.) - 1.5).abs() < 0.01);
+        assert!((c.magnitude_db(24000.) - 3.).abs() < 0.1);
+        assert!(c.magnitude_db(1000.).abs() < 0.1);
+    }
+
+    #[test]
+    fn deesser_band_spans_sibilance() {
+        let c = deesser_band::<f64>(48000).unwrap().coefficients();
+        assert!(c.magnitude_db(6950.).abs() < 1e-9);
+        assert!(c.magnitude_db(6000.) > -3.);
+        assert!(c.magnitude_db(8000.) > -3.);
+        assert!(c.magnitude_db(3000.) < -12.);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{Biquad, BiquadError, FilterSettings};
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+use std::time::Duration;
+
+/// A `rodio::Source` that filters the samples of an inner source on the fly.
+///
+/// Every channel gets its own filter with the same settings. The filters are prepared with the
+/// sample rate of the source, and prepared again whenever the sample rate or the channel count
+/// changes between frames.
+pub struct FilteredSource<S: Source> {
+    source: S,
+    settings: FilterSettings,
+    filters: Vec<Biquad<Sample>>,
+    sample_rate: SampleRate,
+    /// the channel of the next sample
+    channel: usize,
+}
+
+impl<S: Source> FilteredSource<S> {
+    pub fn new(source: S, settings: FilterSettings) -> Result<Self, BiquadError> {
+        let mut filtered = Self {
+            sample_rate: source.sample_rate(),
+            source,
+            settings,
+            filters: Vec::new(),
+            channel: 0,
+        };
+        filtered.prepare()?;
+        Ok(filtered)
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.source
+    }
+
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    fn prepare(&mut self) -> Result<(), BiquadError> {
+        self.sample_rate = self.source.sample_rate();
+        let mut filter = Biquad::default();
+        filter.prepare(self.sample_rate.get())?;
+        filter.set(
+            self.settings.filter_type,
+            self.settings.frequency,
+            self.settings.gain_db,
+            self.settings.q,
+        )?;
+        self.filters = vec![filter; self.source.channels().get() as usize];
+        Ok(())
+    }
+}
+
+impl<S: Source> Iterator for FilteredSource<S> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        if self.channel == 0
+            && (self.source.sample_rate() != self.sample_rate
+                || self.source.channels().get() as usize != self.filters.len())
+        {
+            // settings that worked before can fail at a lower sample rate, then the samples
+            // pass unfiltered until the format changes again
+            if self.prepare().is_err() {
+                self.filters.clear();
+            }
+        }
+
+        let sample = self.source.next()?;
+        let filtered = match self.filters.get_mut(self.channel) {
+            Some(filter) => filter.tick(sample),
+            None => sample,
+        };
+        self.channel = (self.channel + 1) % self.source.channels().get() as usize;
+        Some(filtered)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.source.size_hint()
+    }
+}
+
+impl<S: Source> Source for FilteredSource<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FilterType;
+    use std::num::NonZero;
+
+    /// Interleaved stereo with DC on the left and a sine at Nyquist on the right.
+    struct Dummy {
+        remaining: usize,
+    }
+
+    impl Iterator for Dummy {
+        type Item = Sample;
+
+        fn next(&mut self) -> Option<Sample> {
+            self.remaining = self.remaining.checked_sub(1)?;
+            let frame = self.remaining / 2;
+            Some(match (self.remaining % 2, frame % 2) {
+                (1, _) => 0.5,
+                (_, 0) => 0.5,
+                _ => -0.5,
+            })
+        }
+    }
+
+    impl Source for Dummy {
+        fn current_span_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> ChannelCount {
+            NonZero::new(2).unwrap()
+        }
+
+        fn sample_rate(&self) -> SampleRate {
+            NonZero::new(44100).unwrap()
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn filters_each_channel_and_keeps_format() {
+        let settings = FilterSettings {
+            filter_type: FilterType::Lowpass,
+            frequency: 1000.,
+            gain_db: 0.,
+            q: 0.707,
+        };
+        let filtered = FilteredSource::new(Dummy { remaining: 8820 }, settings).unwrap();
+        assert_eq!(filtered.channels().get(), 2);
+        assert_eq!(filtered.sample_rate().get(), 44100);
+
+        let samples: Vec<Sample> = filtered.collect();
+        assert_eq!(samples.len(), 8820);
+        // the lowpass passes the DC on the left and removes the Nyquist sine on the right
+        let (left, right) = (samples[8818], samples[8819]);
+        assert!((left - 0.5).abs() < 1e-3);
+        assert!(right.abs() < 1e-3);
+    }
+}
@@ -0,0 +1,131 @@
+//! Test signals for verifying filters.
+
+use crate::Biquad;
+use num_traits::Float;
+use std::f64::consts::PI;
+
+pub fn sine(frequency: f64, sample_rate: u32, len: usize) -> Vec<f64> {
+    (0..len)
+        .map(|n| f64::sin(2. * PI * frequency * n as f64 / sample_rate as f64))
+        .collect()
+}
+
+/// A sine sweeping exponentially from `f0` to `f1` over `len` samples.
+pub fn log_sweep(f0: f64, f1: f64, sample_rate: u32, len: usize) -> Vec<f64> {
+    let duration = len as f64 / sample_rate as f64;
+    let rate = f64::ln(f1 / f0) / duration;
+    (0..len)
+        .map(|n| {
+            let t = n as f64 / sample_rate as f64;
+            f64::sin(2. * PI * f0 * (f64::exp(rate * t) - 1.) / rate)
+        })
+        .collect()
+}
+
+/// `[1, 0, 0, ...]`
+pub fn impulse(len: usize) -> Vec<f64> {
+    (0..len).map(|n| if n == 0 { 1. } else { 0. }).collect()
+}
+
+/// Uniformly distributed noise between -1 and 1, the same seed always gives the same noise.
+pub fn white_noise(len: usize, seed: u64) -> Vec<f64> {
+    // xorshift64*, which gets stuck at a state of zero
+    let mut state = if seed == 0 {
+        0x9e37_79b9_7f4a_7c15
+    } else {
+        seed
+    };
+    (0..len)
+        .map(|_| {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            let random = state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11;
+            2. * random as f64 / (1u64 << 53) as f64 - 1.
+        })
+        .collect()
+}
+
+/// Filters the blocks one after another, keeping the state of `filter` across them, and returns
+/// the output of each block. The outputs joined are the same as filtering the joined input at
+/// once, which makes block continuity easy to check.
+pub fn apply_and_collect<F: Float>(
+    filter: &mut Biquad<F>,
+    windows: impl Iterator<Item = Vec<F>>,
+) -> Vec<Vec<F>> {
+    windows
+        .map(|mut block| {
+            filter.process_in_place(&mut block);
+            block
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zero_crossings(signal: &[f64]) -> usize {
+        signal
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.) != (pair[1] < 0.))
+            .count()
+    }
+
+    #[test]
+    fn blocks_match_single_pass() {
+        let mut filter = Biquad::<f64>::default();
+        filter.prepare(48000).unwrap();
+        filter
+            .set(crate::FilterType::Lowpass, 500., 0., crate::DEFAULT_Q)
+            .unwrap();
+        let input = white_noise(1000, 7);
+
+        let mut single = filter.clone();
+        let mut expected = input.clone();
+        single.process_in_place(&mut expected);
+
+        let sizes = [1, 2, 64, 333, 600];
+        let mut rest = &input[..];
+        let blocks = sizes.iter().map(|size| {
+            let (block, tail) = rest.split_at(*size);
+            rest = tail;
+            block.to_vec()
+        });
+        let outputs = apply_and_collect(&mut filter, blocks);
+
+        assert_eq!(
+            outputs.iter().map(Vec::len).collect::<Vec<_>>(),
+            [1, 2, 64, 333, 600]
+        );
+        assert_eq!(outputs.concat(), expected);
+    }
+
+    #[test]
+    fn sine_has_frequency() {
+        // one second of 440 Hz crosses zero twice per period
+        let crossings = zero_crossings(&sine(440., 48000, 48000));
+        assert!((crossings as i64 - 880).abs() <= 1);
+    }
+
+    #[test]
+    fn sweep_rises_in_frequency() {
+        let sweep = log_sweep(100., 10000., 48000, 48000);
+        assert!(zero_crossings(&sweep[..4800]) < zero_crossings(&sweep[43200..]));
+    }
+
+    #[test]
+    fn impulse_is_unit_sample() {
+        assert_eq!(impulse(4), vec![1., 0., 0., 0.]);
+    }
+
+    #[test]
+    fn white_noise_is_seeded() {
+        let noise = white_noise(10000, 7);
+        assert_eq!(noise, white_noise(10000, 7));
+        assert_ne!(noise, white_noise(10000, 8));
+        assert!(noise.iter().all(|x| (-1. ..1.).contains(x)));
+        let mean = noise.iter().sum::<f64>() / noise.len() as f64;
+        assert!(mean.abs() < 0.05);
+    }
+}
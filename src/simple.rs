@@ -0,0 +1,146 @@
+//! Non-generic functions returning coefficients as plain arrays, without any state.
+//!
+//! This is meant for FFI and WASM bindings where generics and the stateful types are in the
+//! way. All functions return the coefficients normalized to `a0 = 1`, in the order
+//! `[b0, b1, b2, a1, a2]`.
+
+use crate::{BiquadError, Coefficients, FilterType};
+
+pub fn lowpass_coeffs(sample_rate: u32, frequency: f64, q: f64) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Lowpass, sample_rate, frequency, 0., q)
+}
+
+pub fn highpass_coeffs(sample_rate: u32, frequency: f64, q: f64) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Highpass, sample_rate, frequency, 0., q)
+}
+
+/// A bandpass with a gain of Q at the center frequency.
+pub fn bandpass1_coeffs(sample_rate: u32, frequency: f64, q: f64) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Bandpass1, sample_rate, frequency, 0., q)
+}
+
+/// A bandpass with 0 dB gain at the center frequency.
+pub fn bandpass2_coeffs(sample_rate: u32, frequency: f64, q: f64) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Bandpass2, sample_rate, frequency, 0., q)
+}
+
+pub fn notch_coeffs(sample_rate: u32, frequency: f64, q: f64) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Notch, sample_rate, frequency, 0., q)
+}
+
+pub fn allpass_coeffs(sample_rate: u32, frequency: f64, q: f64) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Allpass, sample_rate, frequency, 0., q)
+}
+
+pub fn peak_coeffs(
+    sample_rate: u32,
+    frequency: f64,
+    gain_db: f64,
+    q: f64,
+) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Peak, sample_rate, frequency, gain_db, q)
+}
+
+pub fn lowshelf_coeffs(
+    sample_rate: u32,
+    frequency: f64,
+    gain_db: f64,
+    q: f64,
+) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Lowshelf, sample_rate, frequency, gain_db, q)
+}
+
+pub fn highshelf_coeffs(
+    sample_rate: u32,
+    frequency: f64,
+    gain_db: f64,
+    q: f64,
+) -> Result<[f64; 5], BiquadError> {
+    design(FilterType::Highshelf, sample_rate, frequency, gain_db, q)
+}
+
+fn design(
+    filter_type: FilterType,
+    sample_rate: u32,
+    frequency: f64,
+    gain_db: f64,
+    q: f64,
+) -> Result<[f64; 5], BiquadError> {
+    let mut coefficients = Coefficients::<f64>::default();
+    coefficients.set_sample_rate(sample_rate)?;
+    coefficients.set(filter_type, frequency, gain_db, q)?;
+    Ok([
+        coefficients.b0,
+        coefficients.b1,
+        coefficients.b2,
+        coefficients.a1,
+        coefficients.a2,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Biquad;
+
+    fn generic(
+        filter_type: FilterType,
+        frequency: f64,
+        gain_db: f64,
+        q: f64,
+    ) -> Result<[f64; 5], BiquadError> {
+        let mut filter = Biquad::<f64>::default();
+        filter.prepare(48000)?;
+        filter.set(filter_type, frequency, gain_db, q)?;
+        let c = filter.coefficients();
+        Ok([c.b0, c.b1, c.b2, c.a1, c.a2])
+    }
+
+    #[test]
+    fn simple_matches_generic() {
+        assert_eq!(
+            lowpass_coeffs(48000, 1000., 0.7).unwrap(),
+            generic(FilterType::Lowpass, 1000., 0., 0.7).unwrap()
+        );
+        assert_eq!(
+            highpass_coeffs(48000, 1000., 0.7).unwrap(),
+            generic(FilterType::Highpass, 1000., 0., 0.7).unwrap()
+        );
+        assert_eq!(
+            bandpass1_coeffs(48000, 1000., 2.).unwrap(),
+            generic(FilterType::Bandpass1, 1000., 0., 2.).unwrap()
+        );
+        assert_eq!(
+            bandpass2_coeffs(48000, 1000., 2.).unwrap(),
+            generic(FilterType::Bandpass2, 1000., 0., 2.).unwrap()
+        );
+        assert_eq!(
+            notch_coeffs(48000, 1000., 2.).unwrap(),
+            generic(FilterType::Notch, 1000., 0., 2.).unwrap()
+        );
+        assert_eq!(
+            allpass_coeffs(48000, 1000., 2.).unwrap(),
+            generic(FilterType::Allpass, 1000., 0., 2.).unwrap()
+        );
+        assert_eq!(
+            peak_coeffs(48000, 1000., 3., 2.).unwrap(),
+            generic(FilterType::Peak, 1000., 3., 2.).unwrap()
+        );
+        assert_eq!(
+            lowshelf_coeffs(48000, 1000., 3., 0.7).unwrap(),
+            generic(FilterType::Lowshelf, 1000., 3., 0.7).unwrap()
+        );
+        assert_eq!(
+            highshelf_coeffs(48000, 1000., 3., 0.7).unwrap(),
+            generic(FilterType::Highshelf, 1000., 3., 0.7).unwrap()
+        );
+    }
+
+    #[test]
+    fn simple_propagates_errors() {
+        assert!(matches!(
+            lowpass_coeffs(48000, 30000., 0.7),
+            Err(BiquadError::FrequencyOverNyqist)
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{Biquad, BiquadError};
+use num_complex::Complex;
+use num_traits::Float;
+use rustfft::FftPlanner;
+use std::f64::consts::PI;
+
+const FFT_SIZE: usize = 16384;
+const BIN_SPREAD: usize = 4;
+/// The fewest bins between the harmonics, more than the main lobe of the window is wide.
+const MIN_SPACING_BINS: f64 = 16.;
+
+/// Measures the total harmonic distortion a filter adds to a pure sine.
+///
+/// This is a development utility to validate nonlinear processing, it allocates and runs an FFT
+/// and is not meant to be called from a real time context. The filter has to be prepared and set
+/// already, the sine is generated at its sample rate. The sine is run through the filter until it
+/// settles, then the power of the harmonics is compared to the power of the fundamental. The
+/// result is a ratio, not a percentage, and infinite if the filter removes the fundamental.
+///
+/// `frequency` has to be above 0 Hz and below Nyquist and `amplitude` finite and non-zero. The
+/// FFT gets longer for low frequencies so that the harmonics stay apart.
+pub fn measure_thd<F: Float>(
+    filter: &mut Biquad<F>,
+    frequency: f64,
+    amplitude: f64,
+) -> Result<f64, BiquadError> {
+    let sample_rate = filter
+        .coefficients
+        .sample_rate
+        .to_f64()
+        .ok_or(BiquadError::Fatal)?;
+    if sample_rate == 0. {
+        return Err(BiquadError::NoSampleRate);
+    }
+    if frequency.is_nan() || frequency <= 0. {
+        return Err(BiquadError::FrequencyTooLow);
+    }
+    if 2. * frequency >= sample_rate {
+        return Err(BiquadError::FrequencyOverNyqist);
+    }
+    if !amplitude.is_finite() || amplitude == 0. {
+        return Err(BiquadError::InvalidAmplitude);
+    }
+
+    let fft_size = FFT_SIZE.max((MIN_SPACING_BINS * sample_rate / frequency).ceil() as usize);
+    let fft_size = fft_size.next_power_of_two();
+    let input: Vec<F> = (0..2 * fft_size)
+        .map(|n| {
+            let sample = amplitude * f64::sin(2. * PI * frequency * n as f64 / sample_rate);
+            F::from(sample).unwrap_or_else(F::zero)
+        })
+        .collect();
+    let mut output = vec![F::zero(); input.len()];
+    filter.process(&input, &mut output);
+
+    // only analyze the second half, after the transient has decayed
+    let mut spectrum: Vec<Complex<f64>> = output[fft_size..]
+        .iter()
+        .enumerate()
+        .map(|(n, sample)| {
+            let sample = sample.to_f64().unwrap_or(0.);
+            Complex::new(sample * blackman_harris(n, fft_size), 0.)
+        })
+        .collect();
+    FftPlanner::new()
+        .plan_fft_forward(fft_size)
+        .process(&mut spectrum);
+
+    // the windows around the harmonics must not overlap
+    let bins_per_hz = fft_size as f64 / sample_rate;
+    let spread = BIN_SPREAD.min((frequency * bins_per_hz / 2.) as usize);
+    let band_power = |center: f64| -> f64 {
+        let bin = ((center * bins_per_hz).round() as usize).min(fft_size / 2);
+        let low = bin.saturating_sub(spread);
+        let high = (bin + spread).min(fft_size / 2);
+        spectrum[low..=high].iter().map(|c| c.norm_sqr()).sum()
+    };
+
+    let fundamental = band_power(frequency);
+    let harmonics: f64 = (2..)
+        .map(|harmonic| harmonic as f64 * frequency)
+        .take_while(|harmonic| *harmonic < sample_rate / 2.)
+        .map(band_power)
+        .sum();
+
+    if fundamental == 0. {
+        return Ok(f64::INFINITY);
+    }
+    Ok(f64::sqrt(harmonics / fundamental))
+}
+
+fn blackman_harris(n: usize, len: usize) -> f64 {
+    let x = 2. * PI * n as f64 / len as f64;
+    0.35875 - 0.48829 * f64::cos(x) + 0.14128 * f64::cos(2. * x) - 0.01168 * f64::cos(3. * x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FilterType;
+
+    #[test]
+    fn linear_filter_has_no_thd() {
+        let mut filter = Biquad::<f32>::default();
+        filter.prepare(48000).unwrap();
+        filter.set(FilterType::Peak, 1000., 6., 1.).unwrap();
+
+        let thd = measure_thd(&mut filter, 1000., 0.5).unwrap();
+        assert!(thd < 1e-3, "thd was {thd}");
+
+        // the harmonics of a low fundamental are only a few bins apart in an FFT of FFT_SIZE
+        filter.reset();
+        let thd = measure_thd(&mut filter, 10., 0.5).unwrap();
+        assert!(thd < 1e-3, "thd was {thd}");
+    }
+
+    #[test]
+    fn invalid_sines_are_rejected() {
+        let mut filter = Biquad::<f32>::default();
+        assert!(matches!(
+            measure_thd(&mut filter, 1000., 0.5),
+            Err(BiquadError::NoSampleRate)
+        ));
+        filter.prepare(48000).unwrap();
+        filter.set(FilterType::Lowpass, 1000., 0., 0.707).unwrap();
+        for frequency in [24000., 24100., 30000.] {
+            assert!(matches!(
+                measure_thd(&mut filter, frequency, 0.5),
+                Err(BiquadError::FrequencyOverNyqist)
+            ));
+        }
+        assert!(matches!(
+            measure_thd(&mut filter, 0., 0.5),
+            Err(BiquadError::FrequencyTooLow)
+        ));
+        assert!(matches!(
+            measure_thd(&mut filter, 1000., 0.),
+            Err(BiquadError::InvalidAmplitude)
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{Biquad, BiquadError};
+use num_traits::Float;
+
+/// The gain at either end of the spectrum at full tilt.
+const MAX_TILT_DB: f64 = 6.;
+
+/// A tonal balance control that tilts the whole spectrum around a pivot with a single amount,
+/// from dark at -1 to bright at +1.
+///
+/// A first order low shelf and a complementary high shelf at the pivot turn the lows down as
+/// much as the highs go up, by up to 6 dB each at full tilt. The pivot itself stays at 0 dB.
+#[derive(Debug, Clone)]
+pub struct TiltControl<F: Float> {
+    low: Biquad<F>,
+    high: Biquad<F>,
+    amount: f64,
+}
+
+impl<F: Float> TiltControl<F> {
+    /// Creates a flat tilt control.
+    pub fn new(sample_rate: u32) -> Result<Self, BiquadError> {
+        let mut low = Biquad::default();
+        low.prepare(sample_rate)?;
+        Ok(Self {
+            high: low.clone(),
+            low,
+            amount: 0.,
+        })
+    }
+
+    /// Tilts around `pivot` in Hz, `amount` is clamped to -1 to 1.
+    pub fn set_tilt(&mut self, amount: f64, pivot: f64) -> Result<(), BiquadError> {
+        let amount = amount.clamp(-1., 1.);
+        let gain_db = amount * MAX_TILT_DB;
+        let mut low = self.low.coefficients();
+        let mut high = self.high.coefficients();
+        low.set_first_order_lowshelf(pivot, -gain_db)?;
+        high.set_first_order_highshelf(pivot, gain_db)?;
+        self.low.set_coefficients(low);
+        self.high.set_coefficients(high);
+        self.amount = amount;
+        Ok(())
+    }
+
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+
+    pub fn process_in_place(&mut self, buffer: &mut [F]) {
+        self.low.process_in_place(buffer);
+        self.high.process_in_place(buffer);
+    }
+
+    pub fn reset(&mut self) {
+        self.low.reset();
+        self.high.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn magnitude_db(tilt: &TiltControl<f64>, frequency: f64) -> f64 {
+        tilt.low.coefficients().magnitude_db(frequency)
+            + tilt.high.coefficients().magnitude_db(frequency)
+    }
+
+    #[test]
+    fn zero_tilt_is_flat() {
+        let mut tilt = TiltControl::<f64>::new(48000).unwrap();
+        tilt.set_tilt(0., 1000.).unwrap();
+        let mut buffer = vec![0.5, -0.25, 1., 0.];
+        tilt.process_in_place(&mut buffer);
+        assert_eq!(buffer, [0.5, -0.25, 1., 0.]);
+    }
+
+    #[test]
+    fn positive_tilt_brightens() {
+        let mut tilt = TiltControl::<f64>::new(48000).unwrap();
+        tilt.set_tilt(1., 1000.).unwrap();
+        assert!(magnitude_db(&tilt, 1000.).abs() < 1e-9);
+        assert!((magnitude_db(&tilt, 10.) + MAX_TILT_DB).abs() < 0.1);
+        assert!((magnitude_db(&tilt, 24000.) - MAX_TILT_DB).abs() < 0.1);
+        assert!(magnitude_db(&tilt, 300.) < -1.);
+        assert!(magnitude_db(&tilt, 3000.) > 1.);
+
+        tilt.set_tilt(-2., 1000.).unwrap();
+        assert_eq!(tilt.amount(), -1.);
+        assert!(magnitude_db(&tilt, 3000.) < -1.);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{BiquadError, FilterSettings, FilterType, DEFAULT_Q};
+
+/// The parameters of a WebAudio `BiquadFilterNode`.
+///
+/// `filter_type` is the `type` attribute, `gain` is in dB. For `"lowpass"` and `"highpass"`
+/// WebAudio interprets `q` as the resonance in dB, `20·log10(Q)`, for the other types it is the
+/// linear Q. The shelves ignore it and always have the slope of a Butterworth Q.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WebAudioParams<'a> {
+    pub filter_type: &'a str,
+    pub frequency: f64,
+    pub gain: f64,
+    pub q: f64,
+}
+
+impl FilterSettings {
+    /// The parameters of a WebAudio node with the same response.
+    ///
+    /// Returns `None` for `FilterType::Bandpass1` and `FilterType::Custom`, which a
+    /// `BiquadFilterNode` has no equivalent for. The Q of shelves is dropped, WebAudio shelves
+    /// have the response of a shelf with a Q of `1/√2`.
+    pub fn to_webaudio(&self) -> Option<WebAudioParams<'static>> {
+        let q_db = 20. * f64::log10(self.q);
+        let (filter_type, q) = match self.filter_type {
+            FilterType::Lowpass => ("lowpass", q_db),
+            FilterType::Highpass => ("highpass", q_db),
+            FilterType::Bandpass1 | FilterType::Custom { .. } => return None,
+            FilterType::Bandpass2 => ("bandpass", self.q),
+            FilterType::Notch => ("notch", self.q),
+            FilterType::Allpass => ("allpass", self.q),
+            FilterType::Peak => ("peaking", self.q),
+            FilterType::Lowshelf => ("lowshelf", DEFAULT_Q),
+            FilterType::Highshelf => ("highshelf", DEFAULT_Q),
+        };
+        Some(WebAudioParams {
+            filter_type,
+            frequency: self.frequency,
+            gain: self.gain_db,
+            q,
+        })
+    }
+
+    /// The settings of a WebAudio node, unknown types fail with `BiquadError::InvalidSpec`.
+    pub fn from_webaudio(params: &WebAudioParams) -> Result<Self, BiquadError> {
+        let linear_q = f64::powf(10., params.q / 20.);
+        let (filter_type, q) = match params.filter_type {
+            "lowpass" => (FilterType::Lowpass, linear_q),
+            "highpass" => (FilterType::Highpass, linear_q),
+            "bandpass" => (FilterType::Bandpass2, params.q),
+            "notch" => (FilterType::Notch, params.q),
+            "allpass" => (FilterType::Allpass, params.q),
+            "peaking" => (FilterType::Peak, params.q),
+            "lowshelf" => (FilterType::Lowshelf, DEFAULT_Q),
+            "highshelf" => (FilterType::Highshelf, DEFAULT_Q),
+            name => return Err(BiquadError::InvalidSpec(name.to_string())),
+        };
+        Ok(Self {
+            filter_type,
+            frequency: params.frequency,
+            gain_db: params.gain,
+            q,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peak_round_trips() {
+        let peak = FilterSettings {
+            filter_type: FilterType::Peak,
+            frequency: 2500.,
+            gain_db: -4.5,
+            q: 1.8,
+        };
+        let params = peak.to_webaudio().unwrap();
+        assert_eq!(
+            params,
+            WebAudioParams {
+                filter_type: "peaking",
+                frequency: 2500.,
+                gain: -4.5,
+                q: 1.8,
+            }
+        );
+        assert_eq!(FilterSettings::from_webaudio(&params).unwrap(), peak);
+    }
+
+    #[test]
+    fn lowpass_q_is_in_db() {
+        let lowpass = FilterSettings {
+            filter_type: FilterType::Lowpass,
+            frequency: 1000.,
+            gain_db: 0.,
+            q: 2.,
+        };
+        let params = lowpass.to_webaudio().unwrap();
+        assert!((params.q - 6.0206).abs() < 1e-4);
+        let back = FilterSettings::from_webaudio(&params).unwrap();
+        assert!((back.q - 2.).abs() < 1e-12);
+
+        let resonance = WebAudioParams {
+            filter_type: "highpass",
+            frequency: 100.,
+            gain: 0.,
+            q: 0.,
+        };
+        assert_eq!(FilterSettings::from_webaudio(&resonance).unwrap().q, 1.);
+    }
+
+    #[test]
+    fn unsupported_types() {
+        let bandpass = FilterSettings {
+            filter_type: FilterType::Bandpass1,
+            frequency: 1000.,
+            gain_db: 0.,
+            q: 2.,
+        };
+        assert!(bandpass.to_webaudio().is_none());
+        let params = WebAudioParams {
+            filter_type: "resonator",
+            frequency: 1000.,
+            gain: 0.,
+            q: 1.,
+        };
+        assert!(matches!(
+            FilterSettings::from_webaudio(&params),
+            Err(BiquadError::InvalidSpec(_))
+        ));
+    }
+}